@@ -1,5 +1,7 @@
+use lexibook::sound_system::phone::Transcription;
 use lexibook::sound_system::MonoSyllableRepartition;
 use std::path::PathBuf;
+use std::str::FromStr;
 use structopt::StructOpt;
 
 #[allow(dead_code)]
@@ -14,6 +16,36 @@ fn variants() -> [&'static str; 6] {
     ]
 }
 
+#[allow(dead_code)]
+fn transcriptions() -> [&'static str; 3] {
+    ["ipa", "xsampa", "kirshenbaum"]
+}
+
+#[allow(dead_code)]
+fn formats() -> [&'static str; 3] {
+    ["text", "markdown", "csv"]
+}
+
+/// Format of a document to transcribe.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocumentFormat {
+    Text,
+    Markdown,
+    Csv,
+}
+
+impl FromStr for DocumentFormat {
+    type Err = &'static str;
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "text" | "txt" => Ok(DocumentFormat::Text),
+            "markdown" | "md" => Ok(DocumentFormat::Markdown),
+            "csv" => Ok(DocumentFormat::Csv),
+            _ => Err("unknown format"),
+        }
+    }
+}
+
 #[derive(StructOpt)]
 #[structopt(
     name = "lexibook",
@@ -26,6 +58,18 @@ pub enum Cli {
     Words(Words),
     /// Apply sound transformation on words
     Sounds(Sounds),
+    /// Adapt a word of another language, explaining each step
+    Borrow(Borrow),
+    /// Find the words of a list sounding like a word
+    Search(Search),
+    /// Inflect stems with a paradigm of the morphology
+    Paradigm(Paradigm),
+    /// Generate roots and derive their words with the templates
+    Roots(Roots),
+    /// Transcribe the words of a text, Markdown or CSV document
+    Transcribe(Transcribe),
+    /// Generate names of a kind defined in the file, such as the names of rivers
+    Names(Names),
 }
 #[derive(StructOpt)]
 pub struct Phonology {
@@ -48,6 +92,34 @@ pub struct Words {
     /// Not apply sound transformations
     #[structopt(long = "no-sound-transformations")]
     pub skip_transformation: bool,
+    /// Spell the words with this orthography
+    #[structopt(long)]
+    pub orthography: Option<String>,
+    /// Display every rule instead of one column per stage
+    #[structopt(long)]
+    pub expand: bool,
+    /// The file defines a family: the words go through every language
+    #[structopt(long, conflicts_with_all = &["skip-transformation", "orthography", "expand"])]
+    pub family: bool,
+    /// Reject the words closer than this to the existing words or to each other,
+    /// 1 being about one sound of difference
+    #[structopt(long)]
+    pub min_distance: Option<f64>,
+    /// Existing words, one per line
+    #[structopt(long, requires = "min-distance")]
+    pub against: Option<PathBuf>,
+    /// Generate compounds, the rules refer to the boundary of their elements with `+`
+    #[structopt(long, conflicts_with = "min-distance")]
+    pub compound: bool,
+    /// Number of elements of each compound
+    #[structopt(long, default_value = "2")]
+    pub elements: usize,
+    /// Linking element between the elements of a compound
+    #[structopt(long, requires = "compound")]
+    pub linker: Option<String>,
+    /// Existing words to combine into compounds, one per line. Default to generated words
+    #[structopt(long, requires = "compound")]
+    pub parts: Option<PathBuf>,
     /// Word generation file definition
     pub filename: PathBuf,
 }
@@ -56,12 +128,129 @@ pub struct Words {
 pub struct Sounds {
     #[structopt(flatten)]
     pub common: Common,
+    /// Spell the words with this orthography
+    #[structopt(long)]
+    pub orthography: Option<String>,
+    /// Display every rule instead of one column per stage
+    #[structopt(long)]
+    pub expand: bool,
+    /// Start from this stage, the words are already at its beginning
+    #[structopt(long)]
+    pub from: Option<String>,
+    /// Stop after this stage
+    #[structopt(long)]
+    pub until: Option<String>,
+    /// Reconstruct the words the rules could have come from
+    #[structopt(long, conflicts_with_all = &["from", "until", "expand"])]
+    pub reverse: bool,
+    /// The file defines a family: the words go through every language
+    #[structopt(long, conflicts_with_all = &["from", "until", "expand", "reverse", "orthography"])]
+    pub family: bool,
+    /// Each line is a phrase: `#` matches the boundaries of its words and
+    /// the rules with `#` inside their environment cross them
+    #[structopt(long, conflicts_with_all = &["reverse", "family"])]
+    pub phrases: bool,
     /// Word generation file definition
     pub filename: PathBuf,
     /// Words
     pub input: Option<PathBuf>,
 }
 
+#[derive(StructOpt)]
+pub struct Borrow {
+    #[structopt(flatten)]
+    pub common: Common,
+    /// Word generation file definition
+    pub filename: PathBuf,
+    /// Word to borrow, written in the notation
+    pub word: String,
+}
+
+#[derive(StructOpt)]
+pub struct Search {
+    #[structopt(flatten)]
+    pub common: Common,
+    /// Keep the words at most this far from the word, 1 being about one sound of difference
+    #[structopt(long, default_value = "1")]
+    pub max_distance: f64,
+    /// Word generation file definition
+    pub filename: PathBuf,
+    /// Word to search
+    pub word: String,
+    /// Words
+    pub input: Option<PathBuf>,
+}
+
+#[derive(StructOpt)]
+pub struct Paradigm {
+    #[structopt(flatten)]
+    pub common: Common,
+    /// Word generation file definition
+    pub filename: PathBuf,
+    /// Name of the paradigm
+    pub name: String,
+    /// Stems to inflect
+    #[structopt(required = true)]
+    pub stems: Vec<String>,
+}
+
+#[derive(StructOpt)]
+pub struct Roots {
+    #[structopt(flatten)]
+    pub common: Common,
+    /// Numbers of roots to generate
+    #[structopt(short, long, default_value = "10")]
+    pub numbers: usize,
+    /// Derive the words of this root instead, its consonants separated by `-`
+    #[structopt(long = "root")]
+    pub roots: Vec<String>,
+    /// Word generation file definition
+    pub filename: PathBuf,
+}
+
+#[derive(StructOpt)]
+pub struct Transcribe {
+    #[structopt(flatten)]
+    pub common: Common,
+    /// Format of the document. Default to the one of its extension, or text
+    #[structopt(long, possible_values = &formats(), case_insensitive = true)]
+    pub format: Option<DocumentFormat>,
+    /// Column of the words of a CSV document, its header or its number from 1
+    #[structopt(long, default_value = "1")]
+    pub column: String,
+    /// Write the form after each word, or in a new column of a CSV document,
+    /// instead of replacing the word
+    #[structopt(long)]
+    pub annotate: bool,
+    /// Write the words after the sound transformations instead of their phonetic representation
+    #[structopt(long)]
+    pub sounds: bool,
+    /// Word generation file definition
+    pub filename: PathBuf,
+    /// Document to transcribe
+    pub document: PathBuf,
+}
+
+#[derive(StructOpt)]
+pub struct Names {
+    #[structopt(flatten)]
+    pub common: Common,
+    /// Kind of names, defined by a `names` section of the file
+    #[structopt(long)]
+    pub profile: String,
+    /// Numbers of names to generate
+    #[structopt(short, long, default_value = "10")]
+    pub numbers: usize,
+    /// Repartition of mono syllable names when the profile has no length. Default to "less_frequent"
+    #[structopt(short, long, default_value="less_frequent" , possible_values= &variants(), case_insensitive = true)]
+    pub repartition: MonoSyllableRepartition,
+    /// Spell the names with this orthography
+    #[structopt(long)]
+    pub orthography: Option<String>,
+    /// Word generation file definition
+    pub filename: PathBuf,
+}
+
 #[derive(StructOpt)]
 pub struct Common {
     #[structopt(flatten)]
@@ -72,6 +261,9 @@ pub struct Common {
     /// Display pretty output
     #[structopt(short, long)]
     pub pretty: bool,
+    /// Notation used to display phones
+    #[structopt(long, default_value = "ipa", possible_values = &transcriptions(), case_insensitive = true)]
+    pub notation: Transcription,
 }
 
 impl Cli {
@@ -80,6 +272,12 @@ impl Cli {
             Cli::Phonology(c) => &c.common.verbose,
             Cli::Sounds(c) => &c.common.verbose,
             Cli::Words(c) => &c.common.verbose,
+            Cli::Borrow(c) => &c.common.verbose,
+            Cli::Search(c) => &c.common.verbose,
+            Cli::Paradigm(c) => &c.common.verbose,
+            Cli::Roots(c) => &c.common.verbose,
+            Cli::Transcribe(c) => &c.common.verbose,
+            Cli::Names(c) => &c.common.verbose,
         };
         verbose.log_level()
     }
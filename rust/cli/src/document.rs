@@ -0,0 +1,292 @@
+use crate::cli::DocumentFormat;
+use crate::errors::{Error, Result};
+use std::io;
+use std::path::Path;
+
+/// Starts of the URLs left as they are in a Markdown document.
+const URL_STARTS: [&str; 4] = ["http://", "https://", "mailto:", "www."];
+
+/// Format of a document from its extension, text by default.
+pub fn format(path: &Path) -> DocumentFormat {
+    path.extension()
+        .and_then(|extension| extension.to_str())
+        .and_then(|extension| extension.parse().ok())
+        .unwrap_or(DocumentFormat::Text)
+}
+
+/// Rewrites the text of a document with `transcribe`, which receives
+/// a piece of text and whether the words are annotated or replaced.
+///
+/// The code blocks, code spans, URLs and link destinations of Markdown
+/// are left as they are. In a CSV document only the cells of `column`
+/// are transcribed, the first row being the header; an annotation goes
+/// in a new column named `header` after it.
+pub fn transcribe<F>(
+    document: &'_ str,
+    format: DocumentFormat,
+    (column, annotate, header): (&'_ str, bool, &'_ str),
+    transcribe: F,
+) -> Result<String>
+where
+    F: Fn(&str, bool) -> String,
+{
+    match format {
+        DocumentFormat::Text => Ok(map_lines(document, |line| transcribe(line, annotate))),
+        DocumentFormat::Markdown => {
+            let mut fence: Option<String> = None;
+            Ok(map_lines(document, |line| {
+                let trimmed = line.trim_start();
+                match &fence {
+                    Some(marker) => {
+                        if trimmed.starts_with(marker.as_str()) {
+                            fence = None;
+                        }
+                        line.to_string()
+                    }
+                    None if trimmed.starts_with("```") || trimmed.starts_with("~~~") => {
+                        fence = Some(trimmed[..3].to_string());
+                        line.to_string()
+                    }
+                    None => markdown_line(line, |text| transcribe(text, annotate)),
+                }
+            }))
+        }
+        DocumentFormat::Csv => csv(document, (column, annotate, header), |cell| {
+            transcribe(cell, false)
+        }),
+    }
+}
+
+/// Applies `transcribe` to each line, keeping the line endings.
+fn map_lines<F>(document: &'_ str, mut transcribe: F) -> String
+where
+    F: FnMut(&str) -> String,
+{
+    document
+        .split('\n')
+        .map(|line| match line.strip_suffix('\r') {
+            Some(line) => format!("{}\r", transcribe(line)),
+            None => transcribe(line),
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Transcribes the text of a line, not its code spans, URLs and link destinations.
+fn markdown_line<F>(line: &'_ str, transcribe: F) -> String
+where
+    F: Fn(&str) -> String,
+{
+    let mut result = String::new();
+    let mut text = String::new();
+    let mut rest = line;
+    while let Some(c) = rest.chars().next() {
+        let verbatim = if c == '`' {
+            let ticks = rest.len() - rest.trim_start_matches('`').len();
+            rest[ticks..]
+                .find(&rest[..ticks])
+                .map(|end| end + 2 * ticks)
+        } else if rest.starts_with("](") {
+            rest.find(')').map(|end| end + 1)
+        } else if URL_STARTS.iter().any(|start| rest.starts_with(start))
+            && !text.ends_with(char::is_alphanumeric)
+        {
+            rest.find(|c: char| c.is_whitespace() || c == ')' || c == '>')
+                .or(Some(rest.len()))
+        } else {
+            None
+        };
+        match verbatim {
+            Some(end) => {
+                result.push_str(&transcribe(&text));
+                text.clear();
+                result.push_str(&rest[..end]);
+                rest = &rest[end..];
+            }
+            None => {
+                text.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+    result.push_str(&transcribe(&text));
+    result
+}
+
+fn csv<F>(
+    document: &'_ str,
+    (column, annotate, header): (&'_ str, bool, &'_ str),
+    transcribe: F,
+) -> Result<String>
+where
+    F: Fn(&str) -> String,
+{
+    let mut reader = csv::ReaderBuilder::new()
+        .has_headers(false)
+        .flexible(true)
+        .from_reader(document.as_bytes());
+    let mut writer = csv::WriterBuilder::new().flexible(true).from_writer(vec![]);
+    let mut index = None;
+    for record in reader.records() {
+        let mut cells = record?.iter().map(String::from).collect::<Vec<_>>();
+        let position = match index {
+            Some(position) => position,
+            None => {
+                let position = match column.parse::<usize>() {
+                    Ok(number) if number > 0 && number <= cells.len() => number - 1,
+                    _ => cells
+                        .iter()
+                        .position(|cell| cell.trim() == column)
+                        .ok_or_else(|| Error::UnknownColumn(column.to_string()))?,
+                };
+                index = Some(position);
+                if annotate {
+                    cells.insert(position + 1, header.to_string());
+                }
+                writer.write_record(&cells)?;
+                continue;
+            }
+        };
+        if let Some(cell) = cells.get(position) {
+            let form = transcribe(cell);
+            if annotate {
+                cells.insert(position + 1, form);
+            } else {
+                cells[position] = form;
+            }
+        }
+        writer.write_record(&cells)?;
+    }
+    let bytes = writer
+        .into_inner()
+        .map_err(|e| Error::IO(io::Error::new(e.error().kind(), e.error().to_string())))?;
+    Ok(String::from_utf8_lossy(&bytes).into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn upper(text: &'_ str, annotate: bool) -> String {
+        if annotate {
+            format!("{} [{}]", text, text.to_uppercase())
+        } else {
+            text.to_uppercase()
+        }
+    }
+
+    fn transcribe_document(document: &'_ str, format: DocumentFormat) -> String {
+        transcribe(document, format, ("1", false, "ipa"), upper).unwrap()
+    }
+
+    #[test]
+    fn test_format() {
+        assert_eq!(format(Path::new("words.md")), DocumentFormat::Markdown);
+        assert_eq!(format(Path::new("words.CSV")), DocumentFormat::Csv);
+        assert_eq!(format(Path::new("words")), DocumentFormat::Text);
+    }
+
+    #[test]
+    fn test_text() {
+        assert_eq!(
+            transcribe_document("kata\r\npata\n", DocumentFormat::Text),
+            "KATA\r\nPATA\n"
+        );
+        assert_eq!(
+            transcribe("kata", DocumentFormat::Text, ("1", true, "ipa"), upper).unwrap(),
+            "kata [KATA]"
+        );
+    }
+
+    #[test]
+    fn test_markdown_fenced_blocks() {
+        let document = "kata\n```wgl\nkata\n```\n~~~\nkata ```\n~~~\nkata";
+        assert_eq!(
+            transcribe_document(document, DocumentFormat::Markdown),
+            "KATA\n```wgl\nkata\n```\n~~~\nkata ```\n~~~\nKATA"
+        );
+    }
+
+    #[test]
+    fn test_markdown_code_spans() {
+        assert_eq!(
+            transcribe_document("kata `kata` ``ka`ta`` kata", DocumentFormat::Markdown),
+            "KATA `kata` ``ka`ta`` KATA"
+        );
+        assert_eq!(
+            transcribe_document("kata `kata", DocumentFormat::Markdown),
+            "KATA `KATA"
+        );
+    }
+
+    #[test]
+    fn test_markdown_links_and_urls() {
+        assert_eq!(
+            transcribe_document("[kata](kata.md) kata", DocumentFormat::Markdown),
+            "[KATA](kata.md) KATA"
+        );
+        assert_eq!(
+            transcribe_document(
+                "kata https://kata.org <www.kata.org> mailto:kata@kata.org",
+                DocumentFormat::Markdown
+            ),
+            "KATA https://kata.org <www.kata.org> mailto:kata@kata.org"
+        );
+        assert_eq!(
+            transcribe_document("katawww.kata", DocumentFormat::Markdown),
+            "KATAWWW.KATA"
+        );
+    }
+
+    #[test]
+    fn test_markdown_line_endings() {
+        assert_eq!(
+            transcribe_document(
+                "kata\r\n```\r\nkata\r\n```\r\n`kata`\r\n",
+                DocumentFormat::Markdown
+            ),
+            "KATA\r\n```\r\nkata\r\n```\r\n`kata`\r\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_columns() {
+        let document = "word,meaning\nkata,river\npata,stone\n";
+        assert_eq!(
+            transcribe(document, DocumentFormat::Csv, ("1", false, "ipa"), upper).unwrap(),
+            "word,meaning\nKATA,river\nPATA,stone\n"
+        );
+        assert_eq!(
+            transcribe(
+                document,
+                DocumentFormat::Csv,
+                ("meaning", false, "ipa"),
+                upper
+            )
+            .unwrap(),
+            "word,meaning\nkata,RIVER\npata,STONE\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_annotate() {
+        let document = "word,meaning\nkata,river\npata\n";
+        assert_eq!(
+            transcribe(document, DocumentFormat::Csv, ("word", true, "ipa"), upper).unwrap(),
+            "word,ipa,meaning\nkata,KATA,river\npata,PATA\n"
+        );
+    }
+
+    #[test]
+    fn test_csv_unknown_column() {
+        let document = "word,meaning\nkata,river\n";
+        for column in &["3", "0", "gloss"] {
+            let error = transcribe(document, DocumentFormat::Csv, (column, false, "ipa"), upper)
+                .unwrap_err();
+            match error.downcast_ref::<Error>() {
+                Some(Error::UnknownColumn(name)) => assert_eq!(name, column),
+                _ => panic!("unexpected error: {}", error),
+            }
+        }
+    }
+}
@@ -9,6 +9,11 @@ pub enum Error {
     IO(io::Error),
     Parse(pest::error::Error<lexibook::wgl::Rule>),
     Csv(csv::Error),
+    UnknownOrthography(String),
+    UnknownStage(String),
+    UnknownParadigm(String),
+    UnknownColumn(String),
+    UnknownProfile(String),
 }
 impl fmt::Display for Error {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
@@ -16,6 +21,11 @@ impl fmt::Display for Error {
             Error::IO(e) => e.fmt(f),
             Error::Parse(e) => e.fmt(f),
             Error::Csv(e) => e.fmt(f),
+            Error::UnknownOrthography(name) => write!(f, "Unknown orthography: {}", name),
+            Error::UnknownStage(name) => write!(f, "Unknown stage: {}", name),
+            Error::UnknownParadigm(name) => write!(f, "Unknown paradigm: {}", name),
+            Error::UnknownColumn(name) => write!(f, "Unknown column: {}", name),
+            Error::UnknownProfile(name) => write!(f, "Unknown name profile: {}", name),
         }
     }
 }
@@ -44,6 +54,11 @@ impl error::Error for Error {
             Error::IO(e) => e.source(),
             Error::Parse(e) => e.source(),
             Error::Csv(e) => e.source(),
+            Error::UnknownOrthography(_) => None,
+            Error::UnknownStage(_) => None,
+            Error::UnknownParadigm(_) => None,
+            Error::UnknownColumn(_) => None,
+            Error::UnknownProfile(_) => None,
         }
     }
 }
@@ -7,17 +7,21 @@ extern crate fern;
 extern crate pest;
 
 mod cli;
+mod document;
 mod errors;
 mod output;
 use cli::*;
 use errors::*;
 use fern::colors::{Color, ColoredLevelConfig};
+use lexibook::sound_system::phone::Transcription;
 use lexibook::sound_system::rules::Transformation;
-use lexibook::sound_system::SoundSystem;
+use lexibook::sound_system::{Family, Reconstruction, SoundSystem};
+use std::cell::RefCell;
+use std::collections::HashMap;
 use std::convert::From;
 use std::fs::{self, File};
 use std::io::{self, BufRead, Write};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use structopt::StructOpt;
 
 fn setup_log(verbosity: Option<log::Level>) {
@@ -67,9 +71,15 @@ pub fn main() {
     let opt = Cli::from_args();
     setup_log(opt.verbosity());
     let result = match opt {
-        Cli::Phonology(command) => phonology(command.filename),
+        Cli::Phonology(command) => phonology(command.filename, command.common.notation),
         Cli::Words(command) => words(command),
         Cli::Sounds(command) => sounds(command),
+        Cli::Borrow(command) => borrow(command),
+        Cli::Search(command) => search(command),
+        Cli::Paradigm(command) => paradigm(command),
+        Cli::Roots(command) => roots(command),
+        Cli::Transcribe(command) => transcribe(command),
+        Cli::Names(command) => names(command),
     };
 
     match result {
@@ -78,12 +88,13 @@ pub fn main() {
     }
 }
 
-fn phonology(filename: PathBuf) -> Result<()> {
+fn phonology(filename: PathBuf, notation: Transcription) -> Result<()> {
     let input = Box::leak(fs::read_to_string(filename).unwrap().into_boxed_str());
     lexibook::sound_system::from_string(input)
         .map_err(From::from)
         .map(|sound_system| {
-            let (consonants_table, vowel_table) = output::create_phonology_tables(sound_system);
+            let (consonants_table, vowel_table) =
+                output::create_phonology_tables(sound_system, notation);
             println!("Consonants\n");
             consonants_table.printstd();
             println!("\nVowels\n");
@@ -92,36 +103,107 @@ fn phonology(filename: PathBuf) -> Result<()> {
 }
 
 fn words(command: Words) -> Result<()> {
+    if command.family {
+        let mut family = read_family(&command.filename)?;
+        let words = generate_words(family.proto(), &command)?;
+        let cognates = family.cognates(words);
+        return print_cognates(
+            (command.common.pretty, command.common.notation),
+            &family,
+            cognates,
+            command.common.output,
+        );
+    }
     let input = Box::leak(
         fs::read_to_string(command.filename.as_path())
             .unwrap()
             .into_boxed_str(),
     );
 
-    let numbers = command.numbers;
-    let repartition = command.repartition;
     let pretty = command.common.pretty;
-    let maybe_output = command.common.output;
+    let notation = command.common.notation;
+    let maybe_output = command.common.output.clone();
     let skip_transformation = command.skip_transformation;
+    let orthography = command.orthography.clone();
+    let expand = command.expand;
 
     lexibook::sound_system::from_string(input)
         .map_err(From::from)
         .and_then(|mut sound_system| {
-            let words = sound_system.generate_words(numbers, repartition);
+            check_orthography(&sound_system, orthography.as_ref())?;
+            let words = generate_words(&sound_system, &command)?;
             let transformations = if skip_transformation {
                 Transformation {
-                    output: words.clone(),
+                    output: words
+                        .iter()
+                        .map(|word| vec![sound_system.remove_boundaries(word)])
+                        .collect(),
                     ..Default::default()
                 }
             } else {
                 sound_system.sound_trasformation(words.clone())
             };
-            pretty_print(pretty, &sound_system, words, transformations, maybe_output)
+            pretty_print(
+                (pretty, expand),
+                notation,
+                &sound_system,
+                words,
+                transformations,
+                orthography,
+                maybe_output,
+            )
         })
         .map_err(From::from)
 }
 
+/// Generates the words, far enough from the existing ones with `--min-distance`.
+fn generate_words(sound_system: &SoundSystem, command: &Words) -> Result<Vec<String>> {
+    if command.compound {
+        let lexicon = match &command.parts {
+            Some(path) => read_words(Some(path.clone()))?
+                .into_iter()
+                .filter(|word| !word.trim().is_empty())
+                .collect(),
+            None => vec![],
+        };
+        return Ok(sound_system.generate_compounds(
+            command.numbers,
+            command.repartition,
+            (command.elements, command.linker.as_deref()),
+            &lexicon,
+        ));
+    }
+    match command.min_distance {
+        Some(min_distance) => {
+            let lexicon = match &command.against {
+                Some(path) => read_words(Some(path.clone()))?
+                    .into_iter()
+                    .filter(|word| !word.trim().is_empty())
+                    .collect(),
+                None => vec![],
+            };
+            Ok(sound_system.generate_distinct_words(
+                command.numbers,
+                command.repartition,
+                &lexicon,
+                min_distance,
+            ))
+        }
+        None => Ok(sound_system.generate_words(command.numbers, command.repartition)),
+    }
+}
+
 fn sounds(command: Sounds) -> Result<()> {
+    if command.family {
+        let mut family = read_family(&command.filename)?;
+        let cognates = family.cognates(read_words(command.input)?);
+        return print_cognates(
+            (command.common.pretty, command.common.notation),
+            &family,
+            cognates,
+            command.common.output,
+        );
+    }
     let input = Box::leak(
         fs::read_to_string(command.filename.as_path())
             .unwrap()
@@ -129,39 +211,542 @@ fn sounds(command: Sounds) -> Result<()> {
     );
 
     let pretty = command.common.pretty;
+    let notation = command.common.notation;
     let maybe_output = command.common.output;
     let input_words = command.input;
+    let orthography = command.orthography;
+    let expand = command.expand;
+    let from = command.from;
+    let until = command.until;
+    let reverse = command.reverse;
+    let phrases = command.phrases;
 
     lexibook::sound_system::from_string(input)
         .map_err(From::from)
         .and_then(|mut sound_system| {
-            let stdin = io::stdin();
-            let words: Result<Vec<String>> = match input_words {
-                Some(filename) => File::open(filename).map_err(From::from).map(|file| {
-                    io::BufReader::new(file)
-                        .lines()
-                        .map(|ln| ln.unwrap())
-                        .collect()
-                }),
-                None => Ok(stdin.lock().lines().map(|ln| ln.unwrap()).collect()),
-            };
+            check_orthography(&sound_system, orthography.as_ref())?;
+            check_stage(&sound_system, from.as_ref())?;
+            check_stage(&sound_system, until.as_ref())?;
+            read_words(input_words).and_then(|words| {
+                if reverse {
+                    let reconstructions = words
+                        .iter()
+                        .map(|word| sound_system.reconstruct(word))
+                        .collect();
+                    return print_reconstructions(
+                        (pretty, notation),
+                        &sound_system,
+                        words,
+                        reconstructions,
+                        orthography,
+                        maybe_output,
+                    );
+                }
+                let transformations = if phrases {
+                    sound_system.phrase_transformation(
+                        words.clone(),
+                        from.as_deref(),
+                        until.as_deref(),
+                    )?
+                } else {
+                    sound_system.stage_transformation(
+                        words.clone(),
+                        from.as_deref(),
+                        until.as_deref(),
+                    )?
+                };
+                pretty_print(
+                    (pretty, expand),
+                    notation,
+                    &sound_system,
+                    words,
+                    transformations,
+                    orthography,
+                    maybe_output,
+                )
+            })
+        })
+}
+
+fn borrow(command: Borrow) -> Result<()> {
+    let input = Box::leak(
+        fs::read_to_string(command.filename.as_path())
+            .unwrap()
+            .into_boxed_str(),
+    );
+    let notation = command.common.notation;
+    let sound_system = lexibook::sound_system::from_string(input).map_err(Error::from)?;
+    let adaptation = sound_system.adapt(&command.word, notation);
+    let steps = output::adaptation_steps(&sound_system, notation, &adaptation);
+    let result = format!(
+        "{} /{}/",
+        adaptation.word,
+        sound_system.phonetic_representation(&adaptation.word, notation)
+    );
+    if command.common.pretty {
+        let table = output::create_adaptation_table(&command.word, &steps, &result);
+        let maybe_output = command.common.output.as_ref().and_then(|p| p.to_str());
+        match maybe_output {
+            Some(output) => output::csv(&table, output),
+            None => output::stdout(&table),
+        }
+    } else {
+        let stdout = io::stdout();
+        let mut writer: Box<dyn Write> = match command.common.output {
+            Some(path) => Box::new(File::create(path).unwrap()),
+            None => Box::new(stdout.lock()),
+        };
+        let mut lines = vec![format!("/{}/", command.word)];
+        steps.iter().enumerate().for_each(|(i, (step, word))| {
+            if word.is_empty() {
+                lines.push(format!("{}. {}", i + 1, step));
+            } else {
+                lines.push(format!("{}. {}: {}", i + 1, step, word));
+            }
+        });
+        lines.push(result);
+        writer
+            .write_all(lines.join("\n").as_bytes())
+            .and_then(|_| writer.write(b"\n"))
+            .map(|_| ())
+            .map_err(From::from)
+    }
+}
+
+fn search(command: Search) -> Result<()> {
+    let input = Box::leak(
+        fs::read_to_string(command.filename.as_path())
+            .unwrap()
+            .into_boxed_str(),
+    );
+    let notation = command.common.notation;
+    let sound_system = lexibook::sound_system::from_string(input).map_err(Error::from)?;
+    let words = read_words(command.input)?;
+    let found = sound_system.sounds_like(&command.word, &words, command.max_distance);
+    if command.common.pretty {
+        let table = output::create_search_table(&sound_system, notation, &found);
+        let maybe_output = command.common.output.as_ref().and_then(|p| p.to_str());
+        match maybe_output {
+            Some(output) => output::csv(&table, output),
+            None => output::stdout(&table),
+        }
+    } else {
+        let stdout = io::stdout();
+        let mut writer: Box<dyn Write> = match command.common.output {
+            Some(path) => Box::new(File::create(path).unwrap()),
+            None => Box::new(stdout.lock()),
+        };
+        found.iter().try_for_each(|(word, distance)| {
+            writeln!(
+                writer,
+                "{}\t{}\t{:.2}",
+                word,
+                sound_system.phonetic_representation(word, notation),
+                distance
+            )
+        })?;
+        Ok(())
+    }
+}
+
+fn paradigm(command: Paradigm) -> Result<()> {
+    let input = Box::leak(
+        fs::read_to_string(command.filename.as_path())
+            .unwrap()
+            .into_boxed_str(),
+    );
+    let notation = command.common.notation;
+    let mut sound_system = lexibook::sound_system::from_string(input).map_err(Error::from)?;
+    let inflections = command
+        .stems
+        .iter()
+        .map(|stem| {
+            sound_system
+                .inflect_paradigm(&command.name, stem)
+                .map_err(|_| Error::UnknownParadigm(command.name.clone()))
+        })
+        .collect::<std::result::Result<Vec<_>, _>>()?;
+    if command.common.pretty {
+        let paradigm = sound_system.paradigm(&command.name).unwrap();
+        let table = output::create_paradigm_table(
+            &sound_system,
+            notation,
+            paradigm,
+            &command.stems,
+            &inflections,
+        );
+        let maybe_output = command.common.output.as_ref().and_then(|p| p.to_str());
+        match maybe_output {
+            Some(output) => output::csv(&table, output),
+            None => output::stdout(&table),
+        }
+    } else {
+        let stdout = io::stdout();
+        let mut writer: Box<dyn Write> = match command.common.output {
+            Some(path) => Box::new(File::create(path).unwrap()),
+            None => Box::new(stdout.lock()),
+        };
+        let lines = inflections
+            .iter()
+            .map(|inflections| {
+                inflections
+                    .iter()
+                    .map(|inflection| {
+                        format!(
+                            "{}\t{}\t{}\t/{}/",
+                            inflection.gloss,
+                            inflection.segmented,
+                            output::variants(&inflection.forms),
+                            output::variants_representation(
+                                &sound_system,
+                                notation,
+                                &inflection.forms,
+                            )
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join("\n")
+            })
+            .collect::<Vec<_>>();
+        writer
+            .write_all(lines.join("\n\n").as_bytes())
+            .and_then(|_| writer.write(b"\n"))
+            .map(|_| ())
+            .map_err(From::from)
+    }
+}
+
+fn roots(command: Roots) -> Result<()> {
+    let input = Box::leak(
+        fs::read_to_string(command.filename.as_path())
+            .unwrap()
+            .into_boxed_str(),
+    );
+    let notation = command.common.notation;
+    let mut sound_system = lexibook::sound_system::from_string(input).map_err(Error::from)?;
+    let roots = if command.roots.is_empty() {
+        sound_system.generate_roots(command.numbers)
+    } else {
+        command
+            .roots
+            .iter()
+            .map(|root| root.split('-').map(String::from).collect())
+            .collect()
+    };
+    let derivations = sound_system.derive_words(&roots);
+    if command.common.pretty {
+        let table = output::create_roots_table(&sound_system, notation, &roots, &derivations);
+        let maybe_output = command.common.output.as_ref().and_then(|p| p.to_str());
+        match maybe_output {
+            Some(output) => output::csv(&table, output),
+            None => output::stdout(&table),
+        }
+    } else {
+        let stdout = io::stdout();
+        let mut writer: Box<dyn Write> = match command.common.output {
+            Some(path) => Box::new(File::create(path).unwrap()),
+            None => Box::new(stdout.lock()),
+        };
+        let blocks = roots
+            .iter()
+            .zip(derivations.iter())
+            .map(|(root, derivations)| {
+                let mut lines = vec![root.join("-")];
+                derivations.iter().for_each(|derivation| {
+                    lines.push(format!(
+                        "{}\t{}\t{}\t/{}/",
+                        derivation.template,
+                        derivation.word,
+                        output::variants(&derivation.forms),
+                        output::variants_representation(&sound_system, notation, &derivation.forms)
+                    ))
+                });
+                lines.join("\n")
+            })
+            .collect::<Vec<_>>();
+        writer
+            .write_all(blocks.join("\n\n").as_bytes())
+            .and_then(|_| writer.write(b"\n"))
+            .map(|_| ())
+            .map_err(From::from)
+    }
+}
+
+fn names(command: Names) -> Result<()> {
+    let input = Box::leak(
+        fs::read_to_string(command.filename.as_path())
+            .unwrap()
+            .into_boxed_str(),
+    );
+    let notation = command.common.notation;
+    let mut sound_system = lexibook::sound_system::from_string(input).map_err(Error::from)?;
+    check_orthography(&sound_system, command.orthography.as_ref())?;
+    let profile = sound_system
+        .name_profile(&command.profile)
+        .cloned()
+        .ok_or_else(|| Error::UnknownProfile(command.profile.clone()))?;
+    let words = sound_system.generate_names(&profile, command.numbers, command.repartition);
+    let forms = sound_system.sound_trasformation(words.clone()).output;
+    let names = forms
+        .iter()
+        .map(|forms| {
+            forms
+                .iter()
+                .map(|form| match &command.orthography {
+                    Some(name) => sound_system.spell(form, name).unwrap_or_default(),
+                    None => form.clone(),
+                })
+                .map(|name| profile.format(&name))
+                .collect::<Vec<_>>()
+        })
+        .collect::<Vec<_>>();
+    if command.common.pretty {
+        let table = output::create_names_table(&sound_system, notation, &words, &forms, &names);
+        let maybe_output = command.common.output.as_ref().and_then(|p| p.to_str());
+        match maybe_output {
+            Some(output) => output::csv(&table, output),
+            None => output::stdout(&table),
+        }
+    } else {
+        let stdout = io::stdout();
+        let mut writer: Box<dyn Write> = match command.common.output {
+            Some(path) => Box::new(File::create(path).unwrap()),
+            None => Box::new(stdout.lock()),
+        };
+        let lines = names
+            .iter()
+            .map(|names| output::variants(names))
+            .collect::<Vec<_>>();
+        writer
+            .write_all(lines.join("\n").as_bytes())
+            .and_then(|_| writer.write(b"\n"))
+            .map(|_| ())
+            .map_err(From::from)
+    }
+}
+
+fn transcribe(command: Transcribe) -> Result<()> {
+    let input = Box::leak(
+        fs::read_to_string(command.filename.as_path())
+            .unwrap()
+            .into_boxed_str(),
+    );
+    let sound_system = lexibook::sound_system::from_string(input).map_err(Error::from)?;
+    let text = fs::read_to_string(&command.document)?;
+    let format = command
+        .format
+        .unwrap_or_else(|| document::format(&command.document));
+    let result = transcribe_document(
+        &sound_system,
+        &text,
+        (format, command.column.as_str(), command.annotate),
+        (command.sounds, command.common.notation),
+    )?;
+    match command.common.output {
+        Some(path) => fs::write(path, result)?,
+        None => io::stdout().write_all(result.as_bytes())?,
+    }
+    Ok(())
+}
 
-            words.and_then(|words| {
-                let transformations = sound_system.sound_trasformation(words.clone());
-                pretty_print(pretty, &sound_system, words, transformations, maybe_output)
+/// Transcribes the words of a document, or writes their final forms with `sounds`.
+fn transcribe_document(
+    sound_system: &SoundSystem,
+    text: &'_ str,
+    (format, column, annotate): (DocumentFormat, &'_ str, bool),
+    (sounds, notation): (bool, Transcription),
+) -> Result<String> {
+    let header = if sounds {
+        "Final word".to_string()
+    } else {
+        notation.to_string()
+    };
+    let options = (column, annotate, header.as_str());
+    // The words go through the rules together, they are gathered first.
+    let mut forms = HashMap::new();
+    if sounds {
+        let words = RefCell::new(vec![]);
+        document::transcribe(text, format, options, |text, _| {
+            sound_system.map_words(text, |word| {
+                words.borrow_mut().extend(spelled(sound_system, word));
+                word.to_string()
             })
+        })?;
+        let mut words = words.into_inner();
+        words.sort();
+        words.dedup();
+        // The phoneme rules change the phonemes, the document is spelled with the first ones.
+        let transformation = sound_system.clone().sound_trasformation(words.clone());
+        forms = words
+            .into_iter()
+            .zip(
+                transformation
+                    .output
+                    .iter()
+                    .map(|forms| output::variants(forms)),
+            )
+            .collect();
+    }
+    let form = |word: &str| match forms.get(word) {
+        Some(form) => form.clone(),
+        None => sound_system.phonetic_representation(word, notation),
+    };
+    document::transcribe(text, format, options, |text, annotate| {
+        sound_system.map_words(text, |word| match spelled(sound_system, word) {
+            None => word.to_string(),
+            Some(spelled) if !annotate => form(&spelled),
+            Some(spelled) if sounds => format!("{} ({})", word, form(&spelled)),
+            Some(spelled) => format!("{} /{}/", word, form(&spelled)),
+        })
+    })
+}
+
+/// The word, or the word in lowercase, when the language can spell it.
+fn spelled(sound_system: &SoundSystem, word: &'_ str) -> Option<String> {
+    vec![word.to_string(), word.to_lowercase()]
+        .into_iter()
+        .find(|word| {
+            sound_system
+                .segments(word)
+                .iter()
+                .all(|(_, phones)| !phones.is_empty())
         })
 }
 
+fn read_words(input: Option<PathBuf>) -> Result<Vec<String>> {
+    let stdin = io::stdin();
+    match input {
+        Some(filename) => File::open(filename).map_err(From::from).map(|file| {
+            io::BufReader::new(file)
+                .lines()
+                .map(|ln| ln.unwrap())
+                .collect()
+        }),
+        None => Ok(stdin.lock().lines().map(|ln| ln.unwrap()).collect()),
+    }
+}
+
+/// Reads a family definition, the files of the languages being relative to it.
+fn read_family(filename: &Path) -> Result<Family> {
+    let read = |path: &Path| -> Result<&'static str> {
+        Ok(Box::leak(fs::read_to_string(path)?.into_boxed_str()))
+    };
+    let definition = lexibook::wgl::family_from_string(read(filename)?).map_err(Error::from)?;
+    let directory = filename.parent().unwrap_or_else(|| Path::new(""));
+    let (name, file) = definition.proto;
+    let proto =
+        lexibook::sound_system::from_string(read(&directory.join(file))?).map_err(Error::from)?;
+    let mut family = Family::new(name, proto);
+    for (name, parent, file) in definition.branches {
+        let ast = lexibook::wgl::from_string(read(&directory.join(file))?).map_err(Error::from)?;
+        family
+            .add_branch(name, parent, ast)
+            .map_err(|error| format!("{}: {}", name, error))?;
+    }
+    Ok(family)
+}
+
+/// One column per language of the family.
+fn print_cognates(
+    (pretty, notation): (bool, Transcription),
+    family: &Family,
+    cognates: Vec<Transformation>,
+    maybe_output: Option<PathBuf>,
+) -> Result<()> {
+    if pretty {
+        let table = output::create_cognate_table(family, notation, &cognates);
+        let maybe_output = maybe_output.as_ref().and_then(|p| p.to_str());
+        match maybe_output {
+            Some(output) => output::csv(&table, output),
+            None => output::stdout(&table),
+        }
+    } else {
+        let stdout = io::stdout();
+        let mut writer: Box<dyn Write> = match maybe_output {
+            Some(path) => Box::new(File::create(path).unwrap()),
+            None => Box::new(stdout.lock()),
+        };
+        let mut lines = vec![family
+            .languages()
+            .iter()
+            .map(|language| language.name.as_str())
+            .collect::<Vec<_>>()
+            .join("\t")];
+        let size = cognates
+            .first()
+            .map(|words| words.output.len())
+            .unwrap_or(0);
+        (0..size).for_each(|i| {
+            lines.push(
+                cognates
+                    .iter()
+                    .map(|transformation| output::variants(&transformation.output[i]))
+                    .collect::<Vec<_>>()
+                    .join("\t"),
+            )
+        });
+        writer
+            .write_all(lines.join("\n").as_bytes())
+            .and_then(|_| writer.write(b"\n"))
+            .map(|_| ())
+            .map_err(From::from)
+    }
+}
+
 fn pretty_print(
-    pretty: bool,
+    (pretty, expand): (bool, bool),
+    notation: Transcription,
     sound_system: &SoundSystem,
     words: Vec<String>,
     transformations: Transformation,
+    orthography: Option<String>,
+    maybe_output: Option<PathBuf>,
+) -> Result<()> {
+    if pretty {
+        let table = output::create_table(sound_system, notation, words, transformations, expand);
+        let maybe_output = maybe_output.as_ref().and_then(|p| p.to_str());
+        match maybe_output {
+            Some(output) => output::csv(&table, output),
+            None => output::stdout(&table),
+        }
+    } else {
+        let stdout = io::stdout();
+        let mut writer: Box<dyn Write> = match maybe_output {
+            Some(path) => Box::new(File::create(path).unwrap()),
+            None => Box::new(stdout.lock()),
+        };
+        let output = transformations
+            .output
+            .iter()
+            .map(|words| match &orthography {
+                Some(name) => output::variants(
+                    &words
+                        .iter()
+                        .filter_map(|word| sound_system.spell_phrase(word, name))
+                        .collect::<Vec<_>>(),
+                ),
+                None => output::variants(words),
+            })
+            .collect::<Vec<_>>();
+        writer
+            .write_all(output.join("\n").as_bytes())
+            .and_then(|_| writer.write(b"\n"))
+            .map(|_| ())
+            .map_err(From::from)
+    }
+}
+
+fn print_reconstructions(
+    (pretty, notation): (bool, Transcription),
+    sound_system: &SoundSystem,
+    words: Vec<String>,
+    reconstructions: Vec<Vec<Reconstruction>>,
+    orthography: Option<String>,
     maybe_output: Option<PathBuf>,
 ) -> Result<()> {
     if pretty {
-        let table = output::create_table(&sound_system, words, transformations);
+        let table =
+            output::create_reconstruction_table(sound_system, notation, words, reconstructions);
         let maybe_output = maybe_output.as_ref().and_then(|p| p.to_str());
         match maybe_output {
             Some(output) => output::csv(&table, output),
@@ -173,10 +758,64 @@ fn pretty_print(
             Some(path) => Box::new(File::create(path).unwrap()),
             None => Box::new(stdout.lock()),
         };
+        let output = reconstructions
+            .iter()
+            .map(|reconstructions| {
+                let words = reconstructions
+                    .iter()
+                    .filter_map(|reconstruction| match &orthography {
+                        Some(name) => sound_system.spell(&reconstruction.word, name),
+                        None => Some(reconstruction.word.clone()),
+                    })
+                    .collect::<Vec<_>>();
+                output::variants(&words)
+            })
+            .collect::<Vec<_>>();
         writer
-            .write_all(transformations.output.join("\n").as_bytes())
+            .write_all(output.join("\n").as_bytes())
             .and_then(|_| writer.write(b"\n"))
             .map(|_| ())
             .map_err(From::from)
     }
 }
+
+fn check_orthography(sound_system: &SoundSystem, orthography: Option<&String>) -> Result<()> {
+    match orthography {
+        Some(name) if sound_system.orthography(name).is_none() => {
+            Err(Box::new(Error::UnknownOrthography(name.to_string())))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn check_stage(sound_system: &SoundSystem, stage: Option<&String>) -> Result<()> {
+    match stage {
+        Some(name) if sound_system.stage(name).is_none() => {
+            Err(Box::new(Error::UnknownStage(name.to_string())))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transcribe_sounds() {
+        let sound_system =
+            lexibook::sound_system::from_string("letters: a, i, k, x\nrules:\n_: x -> k\nx ~>\n")
+                .unwrap();
+        let transcribe = |annotate| {
+            transcribe_document(
+                &sound_system,
+                "xa kia",
+                (DocumentFormat::Text, "1", annotate),
+                (true, Transcription::Ipa),
+            )
+            .unwrap()
+        };
+        assert_eq!(transcribe(false), "ka kia");
+        assert_eq!(transcribe(true), "xa (ka) kia (kia)");
+    }
+}
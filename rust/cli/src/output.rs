@@ -1,12 +1,38 @@
 use crate::errors::Result;
 use lexibook::sound_system::phone;
-use lexibook::sound_system::rules::{History, Transformation};
-use lexibook::sound_system::SoundSystem;
+use lexibook::sound_system::rules::Transformation;
+use lexibook::sound_system::{
+    Adaptation, AdaptationStep, Derivation, Family, Inflection, Paradigm, Reconstruction,
+    SoundSystem,
+};
 use prettytable::format::Alignment;
 use prettytable::{color, Attr, Cell, Row, Table};
 use std::convert::From;
 use std::fs::File;
 
+/// Variants of a word, as in free variation.
+pub fn variants<S: AsRef<str>>(words: &[S]) -> String {
+    words
+        .iter()
+        .map(AsRef::as_ref)
+        .collect::<Vec<_>>()
+        .join(" ~ ")
+}
+
+/// Phonetic representation of the variants of a word.
+pub fn variants_representation<S: AsRef<str>>(
+    sound_system: &SoundSystem,
+    notation: phone::Transcription,
+    words: &[S],
+) -> String {
+    variants(
+        &words
+            .iter()
+            .map(|word| sound_system.phonetic_representation(word.as_ref(), notation))
+            .collect::<Vec<_>>(),
+    )
+}
+
 pub fn stdout(table: &Table) -> Result<()> {
     table.printstd();
     Ok(())
@@ -59,16 +85,27 @@ pub fn csv(table: &Table, filename: &str) -> Result<()> {
         .map_err(From::from)
 }
 
-pub fn create_phonology_tables(sound_system: SoundSystem) -> (Table, Table) {
+pub fn create_phonology_tables(
+    sound_system: SoundSystem,
+    notation: phone::Transcription,
+) -> (Table, Table) {
     let phonology = sound_system.phonology();
+    let inventory = sound_system.inventory();
     let (consonants, vowels): (Vec<phone::Phone>, Vec<phone::Phone>) = phonology
         .iter()
         .partition(|phone| matches!(phone, phone::Phone::Consonant(_)));
 
-    (consonants_table(consonants), vowels_table(vowels))
+    (
+        consonants_table(inventory, notation, consonants),
+        vowels_table(inventory, notation, vowels),
+    )
 }
 
-fn consonants_table(consonants: Vec<phone::Phone>) -> Table {
+fn consonants_table(
+    inventory: &phone::Inventory,
+    notation: phone::Transcription,
+    consonants: Vec<phone::Phone>,
+) -> Table {
     let mut table = Table::new();
     table.add_row(Row::new(vec![
         Cell::new(""),
@@ -116,8 +153,7 @@ fn consonants_table(consonants: Vec<phone::Phone>) -> Table {
                                     && *place == phone::ConsonantPlace::LabioVelar))
                                 && manner == current_manner =>
                         {
-                            let c: char = (*consonant).into();
-                            c.to_string()
+                            notation.representation(inventory, &[*consonant])
                         }
                         _ => "".to_string(),
                     })
@@ -137,7 +173,11 @@ fn consonants_table(consonants: Vec<phone::Phone>) -> Table {
     table
 }
 
-fn vowels_table(vowels: Vec<phone::Phone>) -> Table {
+fn vowels_table(
+    inventory: &phone::Inventory,
+    notation: phone::Transcription,
+    vowels: Vec<phone::Phone>,
+) -> Table {
     let mut table = Table::new();
     table.add_row(Row::new(vec![
         Cell::new(""),
@@ -158,8 +198,7 @@ fn vowels_table(vowels: Vec<phone::Phone>) -> Table {
                         phone::Phone::Vowel(phone::Vowel {
                             backness, height, ..
                         }) if backness == current_backness && height == current_height => {
-                            let c: char = (*vowel).into();
-                            c.to_string()
+                            notation.representation(inventory, &[*vowel])
                         }
                         _ => "".to_string(),
                     })
@@ -180,71 +219,482 @@ fn vowels_table(vowels: Vec<phone::Phone>) -> Table {
 }
 pub fn create_table(
     sound_system: &SoundSystem,
+    notation: phone::Transcription,
     words: Vec<String>,
     transformations: Transformation,
+    expand: bool,
 ) -> Table {
     let mut table = Table::new();
     let mut header = vec![Cell::new("Generated Word")
         .with_style(Attr::Bold)
         .with_style(Attr::ForegroundColor(color::CYAN))];
-    let rules = transformations
-        .history
-        .iter()
-        .map(|t| &t.rule)
-        .collect::<Vec<_>>();
-    rules.iter().for_each(|rule| {
+    let columns = columns(&transformations, expand);
+    columns.iter().for_each(|(name, ..)| {
         header.push(
-            Cell::new(rule)
+            Cell::new(name)
                 .with_style(Attr::Bold)
                 .with_style(Attr::ForegroundColor(color::BRIGHT_RED)),
         )
     });
-    if !rules.is_empty() {
+    if !columns.is_empty() {
         header.push(
             Cell::new("Final word")
                 .with_style(Attr::Bold)
                 .with_style(Attr::ForegroundColor(color::CYAN)),
         );
         header.push(
-            Cell::new("IPA")
+            Cell::new(&notation.to_string())
                 .with_style(Attr::Bold)
                 .with_style(Attr::ForegroundColor(color::CYAN)),
         );
     }
+    sound_system.orthographies().iter().for_each(|orthography| {
+        header.push(
+            Cell::new(&orthography.name)
+                .with_style(Attr::Bold)
+                .with_style(Attr::ForegroundColor(color::CYAN)),
+        )
+    });
     table.add_row(Row::new(header));
     words.iter().enumerate().for_each(|(i, word)| {
         let mut row = vec![Cell::new(word)];
-        rules.iter().enumerate().for_each(|(rule, _)| {
-            if let Some(History { words: history, .. }) = transformations.history.get(rule) {
-                let previous = if rule == 0 {
-                    word
+        let generated = vec![word.clone()];
+        columns
+            .iter()
+            .enumerate()
+            .for_each(|(column, (_, history, blocked))| {
+                let previous = if column == 0 {
+                    &generated
                 } else {
-                    let History {
-                        words: previous_history,
-                        ..
-                    } = &transformations.history[rule - 1];
-                    &previous_history[i]
+                    &columns[column - 1].1[i]
                 };
                 let current = &history[i];
-                let to_add = if current == previous {
+                let is_blocked = blocked.map(|blocked| blocked[i]).unwrap_or(false);
+                let to_add = if is_blocked {
+                    Cell::new("blocked").with_style(Attr::Italic(true))
+                } else if current == previous {
                     Cell::new("")
                 } else {
-                    Cell::new(current)
+                    Cell::new(&variants(current))
                         .with_style(Attr::Bold)
                         .with_style(Attr::ForegroundColor(color::RED))
                 };
                 row.push(to_add);
-            }
-        });
+            });
         if !transformations.output.is_empty() {
-            let word = &transformations.output[i];
-            row.push(Cell::new(&word).with_style(Attr::Bold));
-            row.push(
-                Cell::new(&format!("/{}/", sound_system.ipa_representation(&word)))
-                    .with_style(Attr::Italic(true)),
-            );
+            let words = &transformations.output[i];
+            row.push(Cell::new(&variants(words)).with_style(Attr::Bold));
+            let representations = words
+                .iter()
+                .map(|word| format!("/{}/", sound_system.phrase_representation(word, notation)))
+                .collect::<Vec<_>>();
+            row.push(Cell::new(&variants(&representations)).with_style(Attr::Italic(true)));
         }
+        let words = transformations.output.get(i).unwrap_or(&generated);
+        sound_system.orthographies().iter().for_each(|orthography| {
+            let spellings = words
+                .iter()
+                .filter_map(|word| sound_system.spell_phrase(word, &orthography.name))
+                .collect::<Vec<_>>();
+            row.push(Cell::new(&variants(&spellings)))
+        });
         table.add_row(Row::new(row));
     });
     table
 }
+
+/// The words in each language of the family with their phonetic representation.
+pub fn create_cognate_table(
+    family: &Family,
+    notation: phone::Transcription,
+    cognates: &[Transformation],
+) -> Table {
+    let mut table = Table::new();
+    let mut header = vec![];
+    family
+        .languages()
+        .iter()
+        .enumerate()
+        .for_each(|(i, language)| {
+            let color = if i == 0 {
+                color::CYAN
+            } else {
+                color::BRIGHT_RED
+            };
+            header.push(
+                Cell::new(&language.name)
+                    .with_style(Attr::Bold)
+                    .with_style(Attr::ForegroundColor(color)),
+            );
+            header.push(
+                Cell::new(&notation.to_string())
+                    .with_style(Attr::Bold)
+                    .with_style(Attr::ForegroundColor(color)),
+            );
+        });
+    table.add_row(Row::new(header));
+    let size = cognates
+        .first()
+        .map(|words| words.output.len())
+        .unwrap_or(0);
+    (0..size).for_each(|i| {
+        let mut row = vec![];
+        family
+            .languages()
+            .iter()
+            .zip(cognates.iter())
+            .for_each(|(language, transformation)| {
+                let words = &transformation.output[i];
+                let representations = words
+                    .iter()
+                    .map(|word| {
+                        format!(
+                            "/{}/",
+                            language
+                                .sound_system
+                                .phonetic_representation(word, notation)
+                        )
+                    })
+                    .collect::<Vec<_>>();
+                row.push(Cell::new(&variants(words)).with_style(Attr::Bold));
+                row.push(Cell::new(&variants(&representations)).with_style(Attr::Italic(true)));
+            });
+        table.add_row(Row::new(row));
+    });
+    table
+}
+
+/// One row per gloss of the first dimension of the paradigm and one column
+/// per combination of the glosses of the others, for each stem.
+pub fn create_paradigm_table(
+    sound_system: &SoundSystem,
+    notation: phone::Transcription,
+    paradigm: &Paradigm,
+    stems: &[String],
+    inflections: &[Vec<Inflection>],
+) -> Table {
+    let mut table = Table::new();
+    let columns = Paradigm {
+        name: paradigm.name.clone(),
+        dimensions: paradigm.dimensions.iter().skip(1).cloned().collect(),
+    }
+    .cells();
+    stems
+        .iter()
+        .zip(inflections.iter())
+        .for_each(|(stem, inflections)| {
+            let mut header = vec![Cell::new(stem)
+                .with_style(Attr::Bold)
+                .with_style(Attr::ForegroundColor(color::BRIGHT_RED))];
+            columns.iter().for_each(|column| {
+                header.push(
+                    Cell::new(&column.join("."))
+                        .with_style(Attr::Bold)
+                        .with_style(Attr::ForegroundColor(color::CYAN)),
+                )
+            });
+            table.add_row(Row::new(header));
+            inflections.chunks(columns.len()).for_each(|row| {
+                let mut cells =
+                    vec![
+                        Cell::new(row[0].cell.first().map(String::as_str).unwrap_or_default())
+                            .with_style(Attr::Bold)
+                            .with_style(Attr::ForegroundColor(color::CYAN)),
+                    ];
+                row.iter().for_each(|inflection| {
+                    cells.push(Cell::new(&variants(
+                        &inflection
+                            .forms
+                            .iter()
+                            .map(|form| {
+                                format!(
+                                    "{} /{}/",
+                                    form,
+                                    sound_system.phonetic_representation(form, notation)
+                                )
+                            })
+                            .collect::<Vec<_>>(),
+                    )))
+                });
+                table.add_row(Row::new(cells));
+            });
+        });
+    table
+}
+
+/// One row per word of each root.
+pub fn create_roots_table(
+    sound_system: &SoundSystem,
+    notation: phone::Transcription,
+    roots: &[Vec<String>],
+    derivations: &[Vec<Derivation>],
+) -> Table {
+    let mut table = Table::new();
+    let header = ["Root", "Template", "Word", "Form", &notation.to_string()]
+        .iter()
+        .map(|title| {
+            Cell::new(title)
+                .with_style(Attr::Bold)
+                .with_style(Attr::ForegroundColor(color::CYAN))
+        })
+        .collect();
+    table.add_row(Row::new(header));
+    roots
+        .iter()
+        .zip(derivations.iter())
+        .for_each(|(root, derivations)| {
+            derivations
+                .iter()
+                .enumerate()
+                .for_each(|(index, derivation)| {
+                    table.add_row(Row::new(vec![
+                        Cell::new(&if index == 0 {
+                            root.join("-")
+                        } else {
+                            String::new()
+                        })
+                        .with_style(Attr::Bold),
+                        Cell::new(&derivation.template),
+                        Cell::new(&derivation.word),
+                        Cell::new(&variants(&derivation.forms)).with_style(Attr::Bold),
+                        Cell::new(&format!(
+                            "/{}/",
+                            variants_representation(sound_system, notation, &derivation.forms)
+                        ))
+                        .with_style(Attr::Italic(true)),
+                    ]));
+                })
+        });
+    table
+}
+
+/// Names with the words they were generated as, their affixes separated by `+`.
+pub fn create_names_table(
+    sound_system: &SoundSystem,
+    notation: phone::Transcription,
+    words: &[String],
+    forms: &[Vec<String>],
+    names: &[Vec<String>],
+) -> Table {
+    let mut table = Table::new();
+    let header = ["Name", "Word", &notation.to_string()]
+        .iter()
+        .map(|title| {
+            Cell::new(title)
+                .with_style(Attr::Bold)
+                .with_style(Attr::ForegroundColor(color::CYAN))
+        })
+        .collect();
+    table.add_row(Row::new(header));
+    words
+        .iter()
+        .zip(forms.iter().zip(names.iter()))
+        .for_each(|(word, (forms, names))| {
+            table.add_row(Row::new(vec![
+                Cell::new(&variants(names)).with_style(Attr::Bold),
+                Cell::new(word),
+                Cell::new(&format!(
+                    "/{}/",
+                    variants_representation(sound_system, notation, forms)
+                )),
+            ]));
+        });
+    table
+}
+
+/// Words sounding like the searched one, the nearest first.
+pub fn create_search_table(
+    sound_system: &SoundSystem,
+    notation: phone::Transcription,
+    found: &[(String, f64)],
+) -> Table {
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Word")
+            .with_style(Attr::Bold)
+            .with_style(Attr::ForegroundColor(color::CYAN)),
+        Cell::new(&notation.to_string())
+            .with_style(Attr::Bold)
+            .with_style(Attr::ForegroundColor(color::CYAN)),
+        Cell::new("Distance")
+            .with_style(Attr::Bold)
+            .with_style(Attr::ForegroundColor(color::CYAN)),
+    ]));
+    found.iter().for_each(|(word, distance)| {
+        table.add_row(Row::new(vec![
+            Cell::new(word).with_style(Attr::Bold),
+            Cell::new(&format!(
+                "/{}/",
+                sound_system.phonetic_representation(word, notation)
+            ))
+            .with_style(Attr::Italic(true)),
+            Cell::new(&format!("{:.2}", distance)),
+        ]));
+    });
+    table
+}
+
+/// Description of each step of an adaptation with the word after it.
+pub fn adaptation_steps(
+    sound_system: &SoundSystem,
+    notation: phone::Transcription,
+    adaptation: &Adaptation,
+) -> Vec<(String, String)> {
+    let inventory = sound_system.inventory();
+    adaptation
+        .steps
+        .iter()
+        .map(|step| match step {
+            AdaptationStep::Substitution { from, to } => (
+                format!(
+                    "Replace /{}/ with the nearest /{}/",
+                    notation.representation(inventory, &[*from]),
+                    notation.representation(inventory, &[*to])
+                ),
+                String::new(),
+            ),
+            AdaptationStep::Dropped(phone) => (
+                format!(
+                    "Drop the diacritic /{}/",
+                    notation.representation(inventory, &[*phone])
+                ),
+                String::new(),
+            ),
+            AdaptationStep::Spelling(word) => ("Spell".to_string(), word.clone()),
+            AdaptationStep::Epenthesis { letter, word } => (
+                format!("Insert {} to fit the syllables", letter),
+                word.clone(),
+            ),
+            AdaptationStep::Deletion { letter, word } => (
+                format!("Delete {} to fit the syllables", letter),
+                word.clone(),
+            ),
+        })
+        .collect()
+}
+
+pub fn create_adaptation_table(
+    word: &'_ str,
+    steps: &[(String, String)],
+    result: &'_ str,
+) -> Table {
+    let mut table = Table::new();
+    table.add_row(Row::new(vec![
+        Cell::new("Step")
+            .with_style(Attr::Bold)
+            .with_style(Attr::ForegroundColor(color::CYAN)),
+        Cell::new("Word")
+            .with_style(Attr::Bold)
+            .with_style(Attr::ForegroundColor(color::CYAN)),
+    ]));
+    table.add_row(Row::new(vec![
+        Cell::new("Borrowed"),
+        Cell::new(&format!("/{}/", word)).with_style(Attr::Italic(true)),
+    ]));
+    steps.iter().for_each(|(step, word)| {
+        table.add_row(Row::new(vec![Cell::new(step), Cell::new(word)]));
+    });
+    table.add_row(Row::new(vec![
+        Cell::new("Result").with_style(Attr::Bold),
+        Cell::new(result).with_style(Attr::Bold),
+    ]));
+    table
+}
+
+/// One row per proto-form of a word, the best fitting first.
+pub fn create_reconstruction_table(
+    sound_system: &SoundSystem,
+    notation: phone::Transcription,
+    words: Vec<String>,
+    reconstructions: Vec<Vec<Reconstruction>>,
+) -> Table {
+    let mut table = Table::new();
+    let mut header = vec![
+        Cell::new("Word")
+            .with_style(Attr::Bold)
+            .with_style(Attr::ForegroundColor(color::CYAN)),
+        Cell::new("Proto-form")
+            .with_style(Attr::Bold)
+            .with_style(Attr::ForegroundColor(color::BRIGHT_RED)),
+        Cell::new(&notation.to_string())
+            .with_style(Attr::Bold)
+            .with_style(Attr::ForegroundColor(color::CYAN)),
+        Cell::new("Fit")
+            .with_style(Attr::Bold)
+            .with_style(Attr::ForegroundColor(color::CYAN)),
+    ];
+    sound_system.orthographies().iter().for_each(|orthography| {
+        header.push(
+            Cell::new(&orthography.name)
+                .with_style(Attr::Bold)
+                .with_style(Attr::ForegroundColor(color::CYAN)),
+        )
+    });
+    table.add_row(Row::new(header));
+    words
+        .iter()
+        .zip(reconstructions.iter())
+        .for_each(|(word, reconstructions)| {
+            if reconstructions.is_empty() {
+                table.add_row(Row::new(vec![
+                    Cell::new(word),
+                    Cell::new("none").with_style(Attr::Italic(true)),
+                ]));
+            }
+            reconstructions
+                .iter()
+                .enumerate()
+                .for_each(|(index, reconstruction)| {
+                    let mut row = vec![
+                        Cell::new(if index == 0 { word } else { "" }),
+                        Cell::new(&reconstruction.word).with_style(Attr::Bold),
+                        Cell::new(&format!(
+                            "/{}/",
+                            sound_system.phonetic_representation(&reconstruction.word, notation)
+                        ))
+                        .with_style(Attr::Italic(true)),
+                        Cell::new(&format!("{:.0}%", reconstruction.fit * 100.0)),
+                    ];
+                    sound_system.orthographies().iter().for_each(|orthography| {
+                        row.push(Cell::new(
+                            &sound_system
+                                .spell(&reconstruction.word, &orthography.name)
+                                .unwrap_or_default(),
+                        ))
+                    });
+                    table.add_row(Row::new(row));
+                });
+        });
+    table
+}
+
+/// Name, words and blocked words of a column.
+type Column<'a> = (&'a str, &'a Vec<Vec<String>>, Option<&'a Vec<bool>>);
+
+/// One column per rule, the rules of a stage are collapsed
+/// into the words at its end unless `expand` is set.
+fn columns(transformations: &Transformation, expand: bool) -> Vec<Column<'_>> {
+    let history = &transformations.history;
+    history
+        .iter()
+        .enumerate()
+        .filter_map(|(i, current)| match &current.stage {
+            Some(stage) if !expand => {
+                let is_last = history
+                    .get(i + 1)
+                    .map(|next| next.stage.as_ref() != Some(stage))
+                    .unwrap_or(true);
+                if is_last {
+                    Some((stage.as_str(), &current.words, None))
+                } else {
+                    None
+                }
+            }
+            _ => Some((
+                current.rule.as_str(),
+                &current.words,
+                Some(&current.blocked),
+            )),
+        })
+        .collect()
+}
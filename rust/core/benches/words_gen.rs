@@ -39,7 +39,11 @@ fn run_with_rule(numbers: usize) -> Vec<String> {
         .map(|mut sound_system| {
             let words = sound_system.generate_words(numbers, MonoSyllableRepartition::LessFrequent);
             let result = sound_system.sound_trasformation(words);
-            result.output
+            result
+                .output
+                .into_iter()
+                .filter_map(|variants| variants.into_iter().next())
+                .collect()
         })
         .unwrap()
 }
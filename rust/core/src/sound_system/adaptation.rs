@@ -0,0 +1,267 @@
+use super::phone::{Backness, Height, Phone, Roundness, Transcription, Vowel};
+use super::{Condition, Segment, SoundSystem};
+use std::cmp::Ordering;
+
+/// Step of the adaptation of a borrowed word.
+#[derive(Debug, Serialize, PartialEq, Clone)]
+pub enum AdaptationStep {
+    /// A phone missing from the language replaced by the nearest one.
+    Substitution { from: Phone, to: Phone },
+    /// A diacritic the language does not use.
+    Dropped(Phone),
+    /// Letters of the phones.
+    Spelling(String),
+    /// A vowel inserted so that the word fits the syllables.
+    Epenthesis { letter: String, word: String },
+    /// A letter deleted so that the word fits the syllables.
+    Deletion { letter: String, word: String },
+}
+
+/// Borrowed word with the steps of its adaptation.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Adaptation {
+    pub word: String,
+    pub steps: Vec<AdaptationStep>,
+}
+
+/// Words are made to fit the syllables with at most this number
+/// of insertions and deletions.
+const MAX_REPAIRS: usize = 3;
+
+impl SoundSystem {
+    /// Adapts a word of another language written in `transcription`.
+    ///
+    /// Each phone missing from the phonology becomes the nearest one,
+    /// the phones are spelled with the letters of `phonemes:`, then vowels
+    /// are inserted or letters deleted until the word fits `syllables:`.
+    pub fn adapt(&self, word: &'_ str, transcription: Transcription) -> Adaptation {
+        let mut steps = vec![];
+        let phonology = self.phonology();
+        let diacritics = self
+            .phonemes_sorted()
+            .iter()
+            .flat_map(|(_, list)| list.iter().flat_map(|(phones, _)| phones.iter()))
+            .filter(|phone| matches!(phone, Phone::Diacritic(_)))
+            .copied()
+            .collect::<Vec<_>>();
+        let phones = transcription
+            .phones(self.inventory(), word)
+            .into_iter()
+            .filter_map(|phone| match phone {
+                Phone::Diacritic(_) if diacritics.contains(&phone) => Some(phone),
+                Phone::Diacritic(_) => {
+                    steps.push(AdaptationStep::Dropped(phone));
+                    None
+                }
+                _ if phonology.contains(&phone) => Some(phone),
+                _ => match phonology.iter().min_by_key(|other| phone.distance(other)) {
+                    Some(nearest) => {
+                        steps.push(AdaptationStep::Substitution {
+                            from: phone,
+                            to: *nearest,
+                        });
+                        Some(*nearest)
+                    }
+                    None => Some(phone),
+                },
+            })
+            .collect::<Vec<_>>();
+        let mut segments = self.spell_phones(&phones);
+        steps.push(AdaptationStep::Spelling(letters(&segments)));
+        if !self.syllables().is_empty()
+            && !segments.is_empty()
+            && self.pattern_boundaries(&segments).is_none()
+        {
+            match self.repair(&segments) {
+                Some((repaired, repairs)) => {
+                    segments = repaired;
+                    steps.extend(repairs);
+                }
+                None => warn!(
+                    "{} does not fit the syllables after {} repairs",
+                    letters(&segments),
+                    MAX_REPAIRS
+                ),
+            }
+        }
+        Adaptation {
+            word: letters(&segments),
+            steps,
+        }
+    }
+
+    /// Letters of the phones, the longest match first then the most frequent letter.
+    /// A phone without letter is written with its symbol.
+    fn spell_phones(&self, phones: &[Phone]) -> Vec<Segment> {
+        let mut result = vec![];
+        let mut position = 0;
+        while position < phones.len() {
+            let rest = &phones[position..];
+            let found = self
+                .phonemes_sorted()
+                .iter()
+                .flat_map(|(letter, list)| {
+                    list.iter()
+                        .map(move |(phones, condition)| (letter, phones, condition))
+                })
+                .filter(|(_, phones, _)| !phones.is_empty() && rest.starts_with(phones))
+                .max_by(
+                    |(left, left_phones, left_condition),
+                     (right, right_phones, right_condition)| {
+                        left_phones
+                            .len()
+                            .cmp(&right_phones.len())
+                            .then_with(|| {
+                                matches!(left_condition, Condition::Always)
+                                    .cmp(&matches!(right_condition, Condition::Always))
+                            })
+                            .then_with(|| {
+                                self.frequency(left)
+                                    .partial_cmp(&self.frequency(right))
+                                    .unwrap_or(Ordering::Equal)
+                            })
+                            .then_with(|| right.cmp(left))
+                    },
+                );
+            match found {
+                Some((letter, phones, _)) => {
+                    position += phones.len();
+                    result.push((letter.clone(), phones.clone()));
+                }
+                None => {
+                    result.push((
+                        self.inventory().representation(&rest[..1]),
+                        rest[..1].to_vec(),
+                    ));
+                    position += 1;
+                }
+            }
+        }
+        result
+    }
+
+    fn frequency(&self, letter: &'_ str) -> f64 {
+        self.distribution()
+            .iter()
+            .find(|(other, _)| other == letter)
+            .map(|(_, frequency)| *frequency)
+            .unwrap_or(0.0)
+    }
+
+    /// Letter of the vowel nearest to a schwa.
+    fn epenthetic_vowel(&self) -> Option<Segment> {
+        let schwa = Phone::Vowel(Vowel {
+            height: Height::Mid,
+            backness: Backness::Central,
+            roundness: Roundness::UnRounded,
+        });
+        self.phonemes_sorted()
+            .iter()
+            .filter_map(|(letter, list)| {
+                list.iter()
+                    .find(|(phones, _)| matches!(phones.as_slice(), [Phone::Vowel(_)]))
+                    .map(|(phones, _)| (letter, phones))
+            })
+            .min_by_key(|(letter, phones)| (schwa.distance(&phones[0]), (*letter).clone()))
+            .map(|(letter, phones)| (letter.clone(), phones.clone()))
+    }
+
+    /// Fewest insertions of a vowel and deletions making the word fit the syllables,
+    /// insertions being tried first, from the beginning of the word.
+    fn repair(&self, segments: &[Segment]) -> Option<(Vec<Segment>, Vec<AdaptationStep>)> {
+        let vowel = self.epenthetic_vowel();
+        let mut frontier: Vec<(Vec<Segment>, Vec<AdaptationStep>)> =
+            vec![(segments.to_vec(), vec![])];
+        for _ in 0..MAX_REPAIRS {
+            let mut next = vec![];
+            for (current, steps) in &frontier {
+                let insertions = vowel.iter().flat_map(|vowel| {
+                    (0..=current.len()).map(move |position| {
+                        let mut candidate = current.clone();
+                        candidate.insert(position, vowel.clone());
+                        let step = AdaptationStep::Epenthesis {
+                            letter: vowel.0.clone(),
+                            word: letters(&candidate),
+                        };
+                        (candidate, step)
+                    })
+                });
+                let deletions = (0..current.len()).map(|position| {
+                    let mut candidate = current.clone();
+                    let (letter, _) = candidate.remove(position);
+                    let step = AdaptationStep::Deletion {
+                        letter,
+                        word: letters(&candidate),
+                    };
+                    (candidate, step)
+                });
+                for (candidate, step) in insertions.chain(deletions) {
+                    let mut steps = steps.clone();
+                    steps.push(step);
+                    if !candidate.is_empty() && self.pattern_boundaries(&candidate).is_some() {
+                        return Some((candidate, steps));
+                    }
+                    next.push((candidate, steps));
+                }
+            }
+            frontier = next;
+        }
+        None
+    }
+}
+
+fn letters(segments: &[Segment]) -> String {
+    segments.iter().map(|(letter, _)| letter.as_str()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn adapt(syllables: &str, word: &str) -> Adaptation {
+        let input = format!(
+            "letters: a, i, u, p, t, k, s, n, th\nphonemes:\n th /tʰ/\nsyllables: {}\n",
+            syllables
+        );
+        crate::sound_system::from_string(&input)
+            .unwrap()
+            .adapt(word, Transcription::Ipa)
+    }
+
+    fn phone(symbol: char) -> Phone {
+        Phone::try_from(symbol).unwrap()
+    }
+
+    #[test]
+    fn test_substitutions() {
+        let adaptation = adapt("CV CVC", "sɪt");
+        assert_eq!(adaptation.word, "sit");
+        assert_eq!(
+            adaptation.steps,
+            vec![
+                AdaptationStep::Substitution {
+                    from: phone('ɪ'),
+                    to: phone('i')
+                },
+                AdaptationStep::Spelling("sit".to_string())
+            ]
+        );
+        assert_eq!(adapt("CV CVC", "ˈtʰaŋ").word, "than");
+        assert_eq!(adapt("CV CVC", "tʷa").word, "ta");
+    }
+
+    #[test]
+    fn test_repairs() {
+        let adaptation = adapt("CV CVC", "θɪŋk");
+        assert_eq!(adaptation.word, "sinak");
+        assert_eq!(
+            adaptation.steps.last(),
+            Some(&AdaptationStep::Epenthesis {
+                letter: "a".to_string(),
+                word: "sinak".to_string()
+            })
+        );
+        assert_eq!(adapt("CV", "tapk").word, "tapaka");
+    }
+}
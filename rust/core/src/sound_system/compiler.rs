@@ -1,20 +1,35 @@
 use super::distribution::frequency;
+use super::matcher::{group_name, segment_letters, Segments};
 use super::phone::*;
-use super::{Condition, PhonemeCondition, PhonemeDifference, Rule, SoundSystem};
-use crate::wgl::{Environment, TransformationRule, AST};
+use super::rules::{rule_parts, RulePart};
+use super::{
+    Affix, AffixCondition, Allomorph, Condition, Dimension, Exceptions, Grapheme, Harmony,
+    HarmonyKind, NameProfile, Orthography, Paradigm, Pattern, PhonemeCondition, PhonemeDifference,
+    Reduplicant, Reduplication, RootException, Roots, Rule, RuleApplication, RuleMode, ShapePart,
+    SoundSystem, Stage, Stress, StressPlacement, SyllablePosition, Template, TemplatePart, Toneme,
+};
+use crate::wgl::{self, Environment, HarmonySets, PhoneticNotation, TransformationRule, AST};
+use regex::Regex;
 use std::collections::HashMap;
-use std::convert::{Into, TryFrom};
+use std::convert::Into;
+use std::iter::Peekable;
 
 impl SoundSystem {
     pub fn compile(ast: AST<'_>) -> Self {
         let distribution = frequency(&ast.letters);
+        let mut inventory = Inventory::default();
+        inventory.extend(&ast.phones);
+        let transcription = ast
+            .notation
+            .and_then(|notation| notation.parse::<Transcription>().ok())
+            .unwrap_or_default();
         let mut phonemes: HashMap<String, Vec<PhonemeCondition>> = ast
             .letters
             .iter()
             .map(|(letters, _)| {
                 (
                     (*letters).to_string(),
-                    vec![(convert_ipa_letters_to_sounds(letters), Condition::Always)],
+                    vec![(inventory.phones(letters), Condition::Always)],
                 )
             })
             .collect();
@@ -22,10 +37,14 @@ impl SoundSystem {
             .iter()
             .flat_map(|(letter, list)| list.iter().map(move |tuple| (letter, tuple)))
             .for_each(|(letter, (phones, condition))| {
-                let phones = phones
-                    .chars()
-                    .filter_map(|c| Phone::try_from(c).ok())
-                    .collect::<Vec<_>>();
+                let phones = match phones {
+                    PhoneticNotation::Default(notation) => {
+                        transcription.phones(&inventory, notation)
+                    }
+                    PhoneticNotation::XSampa(notation) => {
+                        Transcription::XSampa.phones(&inventory, notation)
+                    }
+                };
                 let entry = phonemes
                     .entry((*letter).to_string())
                     .or_insert_with(Vec::new);
@@ -67,38 +86,509 @@ impl SoundSystem {
             );
         }
 
-        let rules = ast
-            .rules
-            .iter()
-            .map(|rule| match rule {
-                TransformationRule::SoundRule { .. } => Rule::SoundRule {
-                    name: rule.to_string(),
-                    regex: rule_to_regex(&classes, rule),
-                    replacement: rule.output().map(|s| s.to_string()),
-                },
-                TransformationRule::PhonemeRule { .. } => Rule::PhonemeRule {
-                    name: rule.to_string(),
-                    phoneme_differences: rule_to_phoneme_differences(
-                        &classes,
-                        rule.input(),
-                        rule.output(),
-                    ),
-                },
-            })
-            .collect::<Vec<_>>();
+        let tone_names = ast.tones.iter().map(|(name, ..)| *name).collect::<Vec<_>>();
+        let letters = segment_letters(phonemes.keys().cloned().collect(), &classes);
+        let rules = compile_rules(&ast.rules, (&classes, &letters), &inventory, &tone_names);
         let syllables = ast
             .syllables
             .iter()
             .map(|l| l.iter().map(|s| (*s).to_string()).collect())
             .collect();
-        let sound_system = SoundSystem::new(classes, phonemes, syllables, distribution, rules);
+        let mut sound_system = SoundSystem::new(classes, phonemes, syllables, distribution, rules);
+        *sound_system.orthographies_mut() =
+            compile_orthographies(&ast.orthographies, transcription, &inventory);
+        if let Some((placement, secondary)) = ast.stress {
+            match placement.parse::<StressPlacement>() {
+                Ok(placement) => {
+                    *sound_system.stress_mut() = Stress {
+                        placement,
+                        secondary,
+                    }
+                }
+                Err(e) => warn!("Ignoring stress {}: {}", placement, e),
+            }
+        }
+        *sound_system.tones_mut() = ast
+            .tones
+            .iter()
+            .map(|(name, notation, mark, weight)| Toneme {
+                name: (*name).to_string(),
+                phones: transcription.phones(&inventory, notation),
+                mark: mark.unwrap_or(notation).to_string(),
+                weight: *weight,
+            })
+            .collect();
+        // A tone written `/˥/` is rendered with its tone letter, not the diacritic.
+        if transcription == Transcription::Ipa {
+            ast.tones.iter().for_each(|(_, notation, _, _)| {
+                notation.chars().for_each(|symbol| {
+                    if let Some(phone) = inventory.phone(symbol) {
+                        inventory.define(symbol, phone);
+                    }
+                })
+            });
+        }
+        *sound_system.harmonies_mut() = ast
+            .harmonies
+            .iter()
+            .filter_map(|(sets, neutral)| {
+                let kind = match sets {
+                    HarmonySets::Classes(names) => Ok(HarmonyKind::Classes(
+                        names.iter().map(|name| (*name).to_string()).collect(),
+                    )),
+                    HarmonySets::Feature(feature) => feature.parse::<HarmonyKind>(),
+                };
+                match kind {
+                    Ok(kind) => Some(Harmony {
+                        kind,
+                        neutral: neutral.iter().map(|letter| (*letter).to_string()).collect(),
+                    }),
+                    Err(e) => {
+                        warn!("Ignoring harmony: {}", e);
+                        None
+                    }
+                }
+            })
+            .collect();
+        *sound_system.stages_mut() = ast
+            .stages
+            .iter()
+            .map(|(name, rules)| Stage {
+                name: (*name).to_string(),
+                rules: rules.clone(),
+            })
+            .collect();
+        *sound_system.affixes_mut() = compile_affixes(&ast.affixes, sound_system.classes());
+        *sound_system.paradigms_mut() = compile_paradigms(&ast.paradigms);
+        *sound_system.roots_mut() = compile_roots(&ast.roots, &ast.root_exceptions, &ast.templates);
+        *sound_system.names_mut() = compile_names(&ast.names);
+        *sound_system.inventory_mut() = inventory;
         trace!("Sound system compiled: {:#?}", sound_system);
         sound_system
     }
+
+    /// Sound system of a language descending from this one. It starts with
+    /// the phonemes left by the rules of this one, adds the classes of `ast`
+    /// and takes its rules, stages and orthographies.
+    pub fn branch(&self, ast: AST<'_>) -> Self {
+        let mut sound_system = self.clone();
+        self.rules().iter().for_each(|rule| {
+            if let Rule::PhonemeRule {
+                phoneme_differences,
+                ..
+            } = rule
+            {
+                sound_system.update_phoneme(phoneme_differences)
+            }
+        });
+        if !ast.letters.is_empty() || !ast.syllables.is_empty() || !ast.phonemes.is_empty() {
+            warn!("Ignoring the letters, syllables and phonemes of a branch");
+        }
+        ast.classes.iter().for_each(|(name, letters)| {
+            sound_system.classes_mut().insert(
+                (*name).to_string(),
+                letters.iter().map(|letter| (*letter).to_string()).collect(),
+            );
+        });
+        let transcription = ast
+            .notation
+            .and_then(|notation| notation.parse::<Transcription>().ok())
+            .unwrap_or_default();
+        let tone_names = self
+            .tones()
+            .iter()
+            .map(|toneme| toneme.name.as_str())
+            .collect::<Vec<_>>();
+        let letters = sound_system.segment_letters();
+        *sound_system.rules_mut() = compile_rules(
+            &ast.rules,
+            (sound_system.classes(), &letters),
+            self.inventory(),
+            &tone_names,
+        );
+        *sound_system.stages_mut() = ast
+            .stages
+            .iter()
+            .map(|(name, rules)| Stage {
+                name: (*name).to_string(),
+                rules: rules.clone(),
+            })
+            .collect();
+        if !ast.orthographies.is_empty() {
+            *sound_system.orthographies_mut() =
+                compile_orthographies(&ast.orthographies, transcription, self.inventory());
+        }
+        if !ast.affixes.is_empty() {
+            *sound_system.affixes_mut() = compile_affixes(&ast.affixes, sound_system.classes());
+        }
+        if !ast.paradigms.is_empty() {
+            *sound_system.paradigms_mut() = compile_paradigms(&ast.paradigms);
+        }
+        if !ast.roots.is_empty() || !ast.templates.is_empty() {
+            *sound_system.roots_mut() =
+                compile_roots(&ast.roots, &ast.root_exceptions, &ast.templates);
+        }
+        if !ast.names.is_empty() {
+            *sound_system.names_mut() = compile_names(&ast.names);
+        }
+        sound_system
+    }
+}
+
+/// The condition of an allomorph names a class or a letter.
+fn compile_affixes(
+    affixes: &[wgl::Affix<'_>],
+    classes: &HashMap<String, Vec<String>>,
+) -> Vec<Affix> {
+    affixes
+        .iter()
+        .map(|(gloss, allomorphs)| Affix {
+            gloss: (*gloss).to_string(),
+            allomorphs: allomorphs
+                .iter()
+                .map(|allomorph| {
+                    let condition = match allomorph.condition {
+                        Some((position, context)) => {
+                            let letters = classes
+                                .get(context)
+                                .cloned()
+                                .unwrap_or_else(|| vec![context.to_string()]);
+                            if position.eq_ignore_ascii_case("after") {
+                                AffixCondition::After(letters)
+                            } else {
+                                AffixCondition::Before(letters)
+                            }
+                        }
+                        None => AffixCondition::Always,
+                    };
+                    Allomorph {
+                        prefix: allomorph.prefix.to_string(),
+                        infix: allomorph.infix.to_string(),
+                        suffix: allomorph.suffix.to_string(),
+                        reduplication: allomorph.reduplication.map(compile_reduplication),
+                        condition,
+                    }
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn compile_roots(
+    patterns: &[Vec<&'_ str>],
+    exceptions: &[wgl::RootException<'_>],
+    templates: &[wgl::Template<'_>],
+) -> Roots {
+    let exceptions = exceptions
+        .iter()
+        .filter_map(|exception| match exception {
+            wgl::RootException::Identical(left, right) => {
+                match (left.parse::<usize>(), right.parse::<usize>()) {
+                    (Ok(left), Ok(right)) if left > 0 && right > 0 => {
+                        Some(RootException::Identical(left - 1, right - 1))
+                    }
+                    _ => {
+                        warn!("Ignoring root exception {} = {}", left, right);
+                        None
+                    }
+                }
+            }
+            wgl::RootException::Adjacent(left, right) => Some(RootException::Adjacent(
+                (*left).to_string(),
+                (*right).to_string(),
+            )),
+        })
+        .collect();
+    let templates = templates
+        .iter()
+        .filter_map(|(name, pattern)| match template_parts(pattern) {
+            Some(parts) => Some(Template {
+                name: (*name).to_string(),
+                parts,
+            }),
+            None => {
+                warn!("Ignoring template {}: radicals are numbered from 1", name);
+                None
+            }
+        })
+        .collect();
+    Roots {
+        patterns: patterns
+            .iter()
+            .map(|pattern| {
+                pattern
+                    .iter()
+                    .map(|class| class.trim_end_matches('?').to_string())
+                    .collect()
+            })
+            .collect(),
+        exceptions,
+        templates,
+    }
+}
+
+/// A length without weight weighs 1.
+fn compile_names(profiles: &[wgl::NameProfile<'_>]) -> Vec<NameProfile> {
+    profiles
+        .iter()
+        .map(|profile| NameProfile {
+            name: profile.name.to_string(),
+            syllables: profile
+                .syllables
+                .iter()
+                .map(|pattern| pattern.iter().map(|class| (*class).to_string()).collect())
+                .collect(),
+            lengths: profile
+                .lengths
+                .iter()
+                .filter_map(|(number, weight)| match number.parse::<usize>() {
+                    Ok(number) if number > 0 => Some((number, weight.unwrap_or(1.0))),
+                    _ => {
+                        warn!(
+                            "Ignoring the length {} of the names {}",
+                            number, profile.name
+                        );
+                        None
+                    }
+                })
+                .collect(),
+            prefixes: profile.prefixes.iter().map(|p| (*p).to_string()).collect(),
+            suffixes: profile.suffixes.iter().map(|s| (*s).to_string()).collect(),
+            capitalize: profile.capitalize,
+        })
+        .collect()
+}
+
+/// Splits `C1aC2iC3` into letters and radicals, an uppercase letter
+/// followed by the position of the radical in the root.
+fn template_parts(pattern: &'_ str) -> Option<Vec<TemplatePart>> {
+    let mut parts = vec![];
+    let mut letters = String::new();
+    let mut chars = pattern.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c.is_uppercase() && chars.peek().map(char::is_ascii_digit).unwrap_or(false) {
+            if !letters.is_empty() {
+                parts.push(TemplatePart::Letters(letters.clone()));
+                letters.clear();
+            }
+            let mut position = String::new();
+            while let Some(digit) = chars.peek().filter(|digit| digit.is_ascii_digit()) {
+                position.push(*digit);
+                chars.next();
+            }
+            match position.parse::<usize>() {
+                Ok(position) if position > 0 => parts.push(TemplatePart::Radical(position - 1)),
+                _ => return None,
+            }
+        } else {
+            letters.push(c);
+        }
+    }
+    if !letters.is_empty() {
+        parts.push(TemplatePart::Letters(letters));
+    }
+    Some(parts)
+}
+
+/// Reads `[word]-`, `[σ]-`, `-[σ]` or a shape such as `[CV]-` or `[Ce]-`,
+/// an uppercase letter being a class and the other letters fixed.
+fn compile_reduplication(text: &'_ str) -> Reduplication {
+    let prefixed = text.ends_with('-');
+    let shape = text
+        .trim_matches('-')
+        .trim_start_matches('[')
+        .trim_end_matches(']');
+    let reduplicant = match shape.to_lowercase().as_str() {
+        "word" => Reduplicant::Word,
+        "σ" | "syllable" => Reduplicant::Syllable,
+        _ => {
+            let mut parts = vec![];
+            let mut fixed = String::new();
+            shape.chars().for_each(|c| {
+                if c.is_uppercase() {
+                    if !fixed.is_empty() {
+                        parts.push(ShapePart::Fixed(fixed.clone()));
+                        fixed.clear();
+                    }
+                    parts.push(ShapePart::Class(c.to_string()));
+                } else {
+                    fixed.push(c);
+                }
+            });
+            if !fixed.is_empty() {
+                parts.push(ShapePart::Fixed(fixed));
+            }
+            Reduplicant::Shape(parts)
+        }
+    };
+    Reduplication {
+        reduplicant,
+        prefixed,
+    }
+}
+
+fn compile_paradigms(paradigms: &[wgl::Paradigm<'_>]) -> Vec<Paradigm> {
+    paradigms
+        .iter()
+        .map(|(name, dimensions)| Paradigm {
+            name: (*name).to_string(),
+            dimensions: dimensions
+                .iter()
+                .map(|(name, glosses)| Dimension {
+                    name: (*name).to_string(),
+                    glosses: glosses.iter().map(|gloss| (*gloss).to_string()).collect(),
+                })
+                .collect(),
+        })
+        .collect()
 }
 
+fn compile_orthographies(
+    orthographies: &[wgl::Orthography<'_>],
+    transcription: Transcription,
+    inventory: &Inventory,
+) -> Vec<Orthography> {
+    orthographies
+        .iter()
+        .map(|(name, graphemes)| Orthography {
+            name: (*name).to_string(),
+            graphemes: graphemes
+                .iter()
+                .map(|(notation, letters, condition)| Grapheme {
+                    phones: transcription.phones(inventory, notation),
+                    letters: (*letters).to_string(),
+                    condition: condition.clone().into(),
+                })
+                .collect(),
+        })
+        .collect()
+}
+
+fn compile_rules(
+    rules: &[TransformationRule<'_>],
+    (classes, letters): (&HashMap<String, Vec<String>>, &[String]),
+    inventory: &Inventory,
+    tone_names: &[&str],
+) -> Vec<Rule> {
+    rules
+        .iter()
+        .map(|rule| (rule, rule_application(rule), rule_exceptions(rule)))
+        .map(|(rule, application, exceptions)| match rule {
+            TransformationRule::SoundRule {
+                environment,
+                unless,
+                mode,
+                ..
+            } => {
+                let mode = mode
+                    .map(|mode| {
+                        mode.parse::<RuleMode>().unwrap_or_else(|e| {
+                            warn!("Ignoring mode {}: {}", mode, e);
+                            RuleMode::Default
+                        })
+                    })
+                    .unwrap_or_default();
+                let position = match environment {
+                    Environment::Onset => Some(SyllablePosition::Onset),
+                    Environment::Coda => Some(SyllablePosition::Coda),
+                    Environment::Stressed => Some(SyllablePosition::Stressed),
+                    Environment::Unstressed => Some(SyllablePosition::Unstressed),
+                    Environment::Match(pattern) if pattern.contains('$') => {
+                        Some(SyllablePosition::Any)
+                    }
+                    _ => None,
+                };
+                let name = rule.to_string();
+                let pattern = rule_to_pattern((classes, letters), rule.input(), environment);
+                let unless = unless.map(|unless| {
+                    rule_to_pattern(
+                        (classes, letters),
+                        rule.input(),
+                        &Environment::Match(unless),
+                    )
+                });
+                let replacement = rule.output().map(|s| s.to_string());
+                match position {
+                    Some(position) => Rule::SyllableRule {
+                        name,
+                        position,
+                        pattern,
+                        unless,
+                        replacement,
+                        mode,
+                        application,
+                        exceptions,
+                    },
+                    None => Rule::SoundRule {
+                        name,
+                        pattern,
+                        unless,
+                        replacement,
+                        mode,
+                        application,
+                        exceptions,
+                    },
+                }
+            }
+            TransformationRule::ToneRule {
+                environment,
+                input,
+                output,
+                ..
+            } => {
+                let (before, after) = match environment {
+                    Environment::Match(pattern) => {
+                        let mut parts = pattern.splitn(2, '_');
+                        (
+                            tone_environment(parts.next()),
+                            tone_environment(parts.next()),
+                        )
+                    }
+                    _ => (vec![], vec![]),
+                };
+                before
+                    .iter()
+                    .chain(after.iter())
+                    .map(String::as_str)
+                    .chain(vec![*input, output.unwrap_or_default()])
+                    .filter(|name| *name != "#" && !tone_names.contains(name))
+                    .for_each(|name| warn!("Unknown tone {} in {}", name, rule));
+                Rule::ToneRule {
+                    name: rule.to_string(),
+                    before,
+                    input: (*input).to_string(),
+                    after,
+                    output: output.unwrap_or_default().to_string(),
+                    application,
+                    exceptions,
+                }
+            }
+            TransformationRule::ReduplicationRule { reduplication, .. } => {
+                Rule::ReduplicationRule {
+                    name: rule.to_string(),
+                    reduplication: compile_reduplication(reduplication),
+                    application,
+                    exceptions,
+                }
+            }
+            TransformationRule::PhonemeRule { .. } => Rule::PhonemeRule {
+                name: rule.to_string(),
+                phoneme_differences: rule_to_phoneme_differences(
+                    classes,
+                    inventory,
+                    rule.input(),
+                    rule.output(),
+                ),
+            },
+        })
+        .collect()
+}
+
+/// The phones of a phoneme rule such as `Vn ~> Ṽ` are always written in IPA,
+/// whatever the `notation:` of the file, uppercase letters being classes.
 fn rule_to_phoneme_differences(
     classes: &HashMap<String, Vec<String>>,
+    inventory: &Inventory,
     phoneme: &'_ str,
     maybePhones: Option<&'_ str>,
 ) -> Vec<PhonemeDifference> {
@@ -116,11 +606,7 @@ fn rule_to_phoneme_differences(
                 .enumerate()
                 .filter_map(|(idx, phoneme)| {
                     expanded_phones.get(idx).map(|phone_str| {
-                        let phones = phone_str
-                            .chars()
-                            .filter_map(|c| Phone::try_from(c).ok())
-                            .collect();
-                        PhonemeDifference::Upsert(phoneme.to_string(), phones)
+                        PhonemeDifference::Upsert(phoneme.to_string(), inventory.phones(phone_str))
                     })
                 })
                 .collect()
@@ -128,6 +614,12 @@ fn rule_to_phoneme_differences(
     }
 }
 
+fn tone_environment(tones: Option<&'_ str>) -> Vec<String> {
+    tones
+        .map(|tones| tones.chars().map(|c| c.to_string()).collect())
+        .unwrap_or_default()
+}
+
 fn expand(classes: &HashMap<String, Vec<String>>, s: &'_ str) -> Vec<String> {
     s.chars()
         .flat_map(|c| match c {
@@ -162,59 +654,263 @@ fn expand(classes: &HashMap<String, Vec<String>>, s: &'_ str) -> Vec<String> {
         })
 }
 
-fn convert_ipa_letters_to_sounds(letter: &str) -> Vec<Phone> {
-    letter
-        .chars()
-        .filter_map(|c| Phone::try_from(c).ok())
-        .collect()
+fn rule_application(rule: &TransformationRule<'_>) -> RuleApplication {
+    rule.application()
+        .map(|application| {
+            application.parse::<RuleApplication>().unwrap_or_else(|e| {
+                warn!("Ignoring application {} of {}: {}", application, rule, e);
+                RuleApplication::Always
+            })
+        })
+        .unwrap_or_default()
 }
 
-fn rule_to_regex(classes: &HashMap<String, Vec<String>>, rule: &TransformationRule<'_>) -> String {
-    let mut regex = String::new();
-    let mut input = String::from("(?P<input>");
-    rule.input().chars().for_each(|c| match c {
-        c if c.is_uppercase() => {
-            let class_name = c.to_string();
-            if let Some(letters) = classes.get(&class_name as &str) {
-                input.push_str("(?P<");
-                input.push(c);
-                input.push('>');
-                let choice = letters
-                    .iter()
-                    .map(|s| (*s).to_string())
-                    .collect::<Vec<_>>()
-                    .join("|");
-                input.push_str(&choice);
-                input.push(')');
-            };
-        }
-        c => input.push(c),
+fn rule_exceptions(rule: &TransformationRule<'_>) -> Exceptions {
+    let mut exceptions = Exceptions {
+        label: rule.label().map(String::from),
+        ..Default::default()
+    };
+    rule.exceptions().iter().for_each(|exception| {
+        if exception.len() > 1 && exception.starts_with('/') && exception.ends_with('/') {
+            let pattern = &exception[1..exception.len() - 1];
+            match Regex::new(pattern) {
+                Ok(_) => exceptions.pattern = Some(pattern.to_string()),
+                Err(e) => warn!("Ignoring exceptions {} of {}: {}", exception, rule, e),
+            }
+        } else {
+            exceptions.words.push((*exception).to_string())
+        }
     });
-    input.push(')');
-    match rule.environment().unwrap() {
-        Environment::All => regex.push_str(&input),
-        Environment::Match(pattern) => pattern.chars().enumerate().for_each(|(index, c)| match c {
-            '#' if index == 0 => regex.push('^'),
-            '#' => regex.push('$'),
+    exceptions
+}
+
+/// Element of the environment of a rule.
+#[derive(Debug)]
+enum EnvironmentPart {
+    Start,
+    End,
+    Focus,
+    Gap,
+    SyllableBoundary,
+    MorphemeBoundary,
+    WordBoundary,
+    Letter(char),
+    Class(char, Option<String>),
+    Alternation(Vec<Vec<EnvironmentPart>>),
+    Optional(Vec<EnvironmentPart>),
+    /// Any letter but the ones given.
+    Negation(Vec<EnvironmentPart>),
+    Repeat(Box<EnvironmentPart>),
+}
+
+/// Reads the environment until `end`, `,` in an alternation.
+fn environment_parts<I>(chars: &mut Peekable<I>, end: &[char]) -> Vec<EnvironmentPart>
+where
+    I: Iterator<Item = char>,
+{
+    let mut parts = vec![];
+    while let Some(c) = chars.next_if(|c| !end.contains(c)) {
+        let part = match c {
+            '#' if parts.is_empty() => EnvironmentPart::Start,
+            '#' if chars.peek().map(|c| end.contains(c)).unwrap_or(true) => EnvironmentPart::End,
+            // Between two words of a phrase: `_#V`, `V#_`.
+            '#' => EnvironmentPart::WordBoundary,
+            '_' => EnvironmentPart::Focus,
+            '*' => EnvironmentPart::Gap,
+            '$' => EnvironmentPart::SyllableBoundary,
+            '{' => {
+                let mut alternatives = vec![environment_parts(chars, &[',', '}'])];
+                while chars.next() == Some(',') {
+                    alternatives.push(environment_parts(chars, &[',', '}']));
+                }
+                EnvironmentPart::Alternation(alternatives)
+            }
+            '(' => {
+                let optional = environment_parts(chars, &[')']);
+                chars.next();
+                EnvironmentPart::Optional(optional)
+            }
+            '!' => EnvironmentPart::Negation(
+                chars
+                    .next()
+                    .map(|c| match c {
+                        class if class.is_uppercase() => EnvironmentPart::Class(class, None),
+                        letter => EnvironmentPart::Letter(letter),
+                    })
+                    .into_iter()
+                    .collect(),
+            ),
+            '[' => {
+                chars.next_if_eq(&'^');
+                let negated = environment_parts(chars, &[']']);
+                chars.next();
+                EnvironmentPart::Negation(negated)
+            }
+            // After a letter or a class `+` repeats it, elsewhere it is
+            // the boundary of the elements of a compound: `_+P`, `V{+}_`.
+            '+' => match parts.pop() {
+                Some(part)
+                    if !matches!(
+                        part,
+                        EnvironmentPart::Start
+                            | EnvironmentPart::Focus
+                            | EnvironmentPart::Gap
+                            | EnvironmentPart::SyllableBoundary
+                            | EnvironmentPart::MorphemeBoundary
+                            | EnvironmentPart::WordBoundary
+                    ) =>
+                {
+                    // `V+_` matches like `V_`, a boundary was most likely meant.
+                    if chars.peek() == Some(&'_') {
+                        warn!("Reading +_ as a repetition, write {{+}}_ for a morpheme boundary");
+                    }
+                    EnvironmentPart::Repeat(Box::new(part))
+                }
+                Some(part) => {
+                    parts.push(part);
+                    EnvironmentPart::MorphemeBoundary
+                }
+                None => EnvironmentPart::MorphemeBoundary,
+            },
+            c if c.is_whitespace() => continue,
             class if class.is_uppercase() => {
-                let class_name = c.to_string();
-                if let Some(letters) = classes.get(&class_name as &str) {
-                    regex.push('(');
-                    let choice = letters
-                        .iter()
-                        .map(|s| (*s).to_string())
-                        .collect::<Vec<_>>()
-                        .join("|");
-                    regex.push_str(&choice);
-                    regex.push(')');
+                let mut index = String::new();
+                while let Some(digit) = chars.next_if(char::is_ascii_digit) {
+                    index.push(digit);
+                }
+                EnvironmentPart::Class(class, Some(index).filter(|index| !index.is_empty()))
+            }
+            letter => EnvironmentPart::Letter(letter),
+        };
+        parts.push(part);
+    }
+    parts
+}
+
+/// Pattern of a rule in its environment, each class of the input
+/// and indexed class of the environment is captured by its name.
+fn rule_to_pattern(
+    (classes, letters): (&HashMap<String, Vec<String>>, &[String]),
+    rule_input: &'_ str,
+    environment: &Environment<'_>,
+) -> Vec<Pattern> {
+    let mut occurrences: HashMap<String, usize> = HashMap::new();
+    let mut group = |class: char, index: Option<&str>| {
+        let name = format!("{}{}", class, index.unwrap_or_default());
+        let occurrence = occurrences.entry(name.clone()).or_insert(0);
+        *occurrence += 1;
+        group_name(&name, *occurrence)
+    };
+    let mut input = vec![];
+    let mut literal = String::new();
+    rule_parts(rule_input)
+        .into_iter()
+        .for_each(|part| match part {
+            RulePart::Class(class, index) => {
+                input.extend(literal_letters(&literal, letters));
+                literal.clear();
+                if let Some(members) = classes.get(&class.to_string()) {
+                    input.push(Pattern::Letters(members.clone(), Some(group(class, index))));
                 };
             }
-            '_' => regex.push_str(&input),
-            '*' => regex.push_str("(.*?)"),
-            letter => regex.push(letter),
-        }),
+            RulePart::Letter(c) => literal.push(c),
+        });
+    input.extend(literal_letters(&literal, letters));
+    let input = Pattern::Input(input);
+    match environment {
+        Environment::All
+        | Environment::Onset
+        | Environment::Coda
+        | Environment::Stressed
+        | Environment::Unstressed => vec![input],
+        Environment::Match(pattern) => {
+            let parts = environment_parts(&mut pattern.chars().peekable(), &[]);
+            environment_pattern(&parts, (classes, letters), &input, &mut group)
+        }
     }
-    regex
+}
+
+/// Letters of a literal, digraphs being a single letter.
+fn literal_letters(literal: &'_ str, letters: &[String]) -> Vec<Pattern> {
+    let segments = Segments::new(literal, letters);
+    (0..segments.len())
+        .filter_map(|index| segments.get(index))
+        .map(|letter| Pattern::Letter(letter.to_string()))
+        .collect()
+}
+
+fn environment_pattern<F>(
+    parts: &[EnvironmentPart],
+    (classes, letters): (&HashMap<String, Vec<String>>, &[String]),
+    input: &Pattern,
+    group: &mut F,
+) -> Vec<Pattern>
+where
+    F: FnMut(char, Option<&str>) -> String,
+{
+    let mut pattern = vec![];
+    let mut literal = String::new();
+    parts.iter().for_each(|part| {
+        if let EnvironmentPart::Letter(letter) = part {
+            literal.push(*letter);
+            return;
+        }
+        pattern.extend(literal_letters(&literal, letters));
+        literal.clear();
+        match part {
+            EnvironmentPart::Start => pattern.push(Pattern::Start),
+            EnvironmentPart::End => pattern.push(Pattern::End),
+            EnvironmentPart::Focus => pattern.push(input.clone()),
+            EnvironmentPart::Gap => pattern.push(Pattern::Gap),
+            EnvironmentPart::SyllableBoundary => pattern.push(Pattern::SyllableBoundary),
+            EnvironmentPart::MorphemeBoundary => pattern.push(Pattern::MorphemeBoundary),
+            EnvironmentPart::WordBoundary => pattern.push(Pattern::WordBoundary),
+            EnvironmentPart::Letter(_) => (),
+            EnvironmentPart::Class(class, index) => {
+                if let Some(members) = classes.get(&class.to_string()) {
+                    let name = index.as_ref().map(|index| group(*class, Some(index)));
+                    pattern.push(Pattern::Letters(members.clone(), name));
+                };
+            }
+            EnvironmentPart::Alternation(alternatives) => pattern.push(Pattern::Alternation(
+                alternatives
+                    .iter()
+                    .map(|parts| environment_pattern(parts, (classes, letters), input, group))
+                    .collect(),
+            )),
+            EnvironmentPart::Optional(parts) => pattern.push(Pattern::Optional(
+                environment_pattern(parts, (classes, letters), input, group),
+            )),
+            EnvironmentPart::Repeat(part) => pattern.push(Pattern::Repeat(environment_pattern(
+                std::slice::from_ref(part.as_ref()),
+                (classes, letters),
+                input,
+                group,
+            ))),
+            EnvironmentPart::Negation(parts) => {
+                let excluded = parts
+                    .iter()
+                    .flat_map(|part| match part {
+                        EnvironmentPart::Class(class, _) => {
+                            classes.get(&class.to_string()).cloned().unwrap_or_default()
+                        }
+                        EnvironmentPart::Letter(letter) => vec![letter.to_string()],
+                        _ => vec![],
+                    })
+                    .collect::<Vec<_>>();
+                pattern.push(Pattern::Letters(
+                    letters
+                        .iter()
+                        .filter(|letter| !excluded.contains(letter))
+                        .cloned()
+                        .collect(),
+                    None,
+                ))
+            }
+        }
+    });
+    pattern.extend(literal_letters(&literal, letters));
+    pattern
 }
 
 #[cfg(test)]
@@ -223,7 +919,7 @@ mod tests {
     #[test]
     fn test_convert_ipa_letters_to_sounds() {
         let letters = "an";
-        let result = convert_ipa_letters_to_sounds(letters);
+        let result = Inventory::default().phones(letters);
         assert_eq!(
             result,
             vec![
@@ -240,4 +936,26 @@ mod tests {
             ]
         )
     }
+
+    #[test]
+    fn test_phones_override_inventory() {
+        let sound_system = crate::sound_system::from_string(
+            "letters: z, a\nphones:\n  ɿ vowel close central unrounded\nphonemes:\n  z /ɿ/\n",
+        )
+        .unwrap();
+        assert_eq!(sound_system.ipa_representation("za"), "ɿ.a");
+    }
+
+    #[test]
+    fn test_xsampa_phonemes() {
+        let sound_system = crate::sound_system::from_string(
+            "notation: xsampa\nletters: c, a, r\nphonemes:\n  c /tS/\n  r [r\\]\n",
+        )
+        .unwrap();
+        assert_eq!(sound_system.ipa_representation("car"), "tʃaɹ");
+        assert_eq!(
+            sound_system.phonetic_representation("car", Transcription::Kirshenbaum),
+            "tSar"
+        );
+    }
 }
@@ -0,0 +1,125 @@
+use super::{MonoSyllableRepartition, SoundSystem};
+use rand::prelude::*;
+
+/// Boundary between the elements of a compound, written `+` in the
+/// environments of the rules. The rules remove it from the words.
+pub const MORPHEME_BOUNDARY: char = '+';
+
+impl SoundSystem {
+    /// Word without the boundaries of its elements, unless `+` is a letter.
+    pub fn remove_boundaries(&self, word: &'_ str) -> String {
+        let boundary = MORPHEME_BOUNDARY.to_string();
+        if self
+            .phonemes_sorted()
+            .iter()
+            .any(|(letter, _)| letter == &boundary)
+        {
+            word.to_string()
+        } else {
+            word.replace(MORPHEME_BOUNDARY, "")
+        }
+    }
+
+    /// Generates compounds of `elements` words taken from `lexicon`,
+    /// or generated when it is empty, with `linker` between them.
+    pub fn generate_compounds(
+        &self,
+        number: usize,
+        repartition: MonoSyllableRepartition,
+        (elements, linker): (usize, Option<&str>),
+        lexicon: &[String],
+    ) -> Vec<String> {
+        let elements = elements.max(1);
+        let generated;
+        let words = if lexicon.is_empty() {
+            generated = self.generate_words(number * elements, repartition);
+            &generated
+        } else {
+            lexicon
+        };
+        let mut rng = thread_rng();
+        let mut compounds = vec![];
+        for index in 0..number {
+            let parts = if lexicon.is_empty() {
+                words
+                    .iter()
+                    .skip(index * elements)
+                    .take(elements)
+                    .cloned()
+                    .collect::<Vec<_>>()
+            } else {
+                (0..elements)
+                    .filter_map(|_| words.choose(&mut rng).cloned())
+                    .collect()
+            };
+            if parts.len() < elements {
+                break;
+            }
+            let compound = compound(&parts, linker);
+            if !compounds.contains(&compound) {
+                compounds.push(compound);
+            }
+        }
+        compounds
+    }
+}
+
+/// Elements separated by the morpheme boundary, the linking element
+/// being a morpheme of its own.
+pub fn compound(elements: &[String], linker: Option<&str>) -> String {
+    let boundary = MORPHEME_BOUNDARY.to_string();
+    let separator = match linker {
+        Some(linker) if !linker.is_empty() => format!("{}{}{}", boundary, linker, boundary),
+        _ => boundary,
+    };
+    elements.join(&separator)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sound_system() -> SoundSystem {
+        crate::sound_system::from_string(
+            "letters: a, i, u, o, k, g, t, p, m, n, ŋ\nsyllables: CV CVC\nP = p t k\n\
+             rules:\n_+P: n -> m\n_#: n -> ŋ\nV{+}_: k -> g\n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_compound() {
+        let elements = vec!["kan".to_string(), "pu".to_string()];
+        assert_eq!(compound(&elements, None), "kan+pu");
+        assert_eq!(compound(&elements, Some("o")), "kan+o+pu");
+        let lexicon = vec!["kan".to_string()];
+        assert_eq!(
+            sound_system().generate_compounds(
+                3,
+                MonoSyllableRepartition::Always,
+                (2, None),
+                &lexicon
+            ),
+            vec!["kan+kan"]
+        );
+    }
+
+    #[test]
+    fn test_boundary_sandhi() {
+        let mut sound_system = sound_system();
+        let transformation = sound_system.sound_trasformation(vec![
+            "kan+pun".to_string(),
+            "kan".to_string(),
+            "pa+ka".to_string(),
+        ]);
+        assert_eq!(
+            transformation.output,
+            vec![
+                vec!["kampuŋ".to_string()],
+                vec!["kaŋ".to_string()],
+                vec!["paga".to_string()]
+            ]
+        );
+        assert_eq!(transformation.history[0].words[0], vec!["kam+pun"]);
+    }
+}
@@ -0,0 +1,220 @@
+use super::rules::{History, Transformation};
+use super::SoundSystem;
+use crate::wgl::AST;
+
+/// Language of a family with the sound system its words go through.
+#[derive(Debug)]
+pub struct Language {
+    pub name: String,
+    /// Position of the language it descends from, none for the proto-language.
+    pub parent: Option<usize>,
+    pub sound_system: SoundSystem,
+}
+
+/// Proto-language and the languages descending from it,
+/// a language always coming after its parent.
+#[derive(Debug)]
+pub struct Family {
+    languages: Vec<Language>,
+}
+
+impl Family {
+    pub fn new(name: &'_ str, proto: SoundSystem) -> Self {
+        Family {
+            languages: vec![Language {
+                name: name.to_string(),
+                parent: None,
+                sound_system: proto,
+            }],
+        }
+    }
+
+    /// Adds a language descending from `parent`, the proto-language by default.
+    pub fn add_branch(
+        &mut self,
+        name: &'_ str,
+        parent: Option<&'_ str>,
+        ast: AST<'_>,
+    ) -> Result<(), &'static str> {
+        if self.position(name).is_some() {
+            return Err("duplicate language");
+        }
+        let parent = match parent {
+            Some(parent) => self.position(parent).ok_or("unknown parent language")?,
+            None => 0,
+        };
+        let sound_system = self.languages[parent].sound_system.branch(ast);
+        self.languages.push(Language {
+            name: name.to_string(),
+            parent: Some(parent),
+            sound_system,
+        });
+        Ok(())
+    }
+
+    pub fn languages(&self) -> &[Language] {
+        &self.languages
+    }
+
+    pub fn proto(&self) -> &SoundSystem {
+        &self.languages[0].sound_system
+    }
+
+    pub fn proto_mut(&mut self) -> &mut SoundSystem {
+        &mut self.languages[0].sound_system
+    }
+
+    fn position(&self, name: &'_ str) -> Option<usize> {
+        self.languages
+            .iter()
+            .position(|language| language.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Transformation of the words in each language, in the order of `languages`.
+    /// The proto-language applies its rules to `words`, every other language
+    /// applies its rules to the variants of the words of its parent.
+    pub fn cognates(&mut self, words: Vec<String>) -> Vec<Transformation> {
+        let mut result: Vec<Transformation> = vec![];
+        for language in self.languages.iter_mut() {
+            let transformation = match language.parent {
+                None => language.sound_system.sound_trasformation(words.clone()),
+                Some(parent) => {
+                    transform_variants(&mut language.sound_system, &result[parent].output)
+                }
+            };
+            result.push(transformation);
+        }
+        result
+    }
+}
+
+/// Applies the rules to every variant, the variants coming
+/// from the same word are put back together.
+fn transform_variants(sound_system: &mut SoundSystem, words: &[Vec<String>]) -> Transformation {
+    let sizes = words.iter().map(Vec::len).collect::<Vec<_>>();
+    let transformation = sound_system.sound_trasformation(words.concat());
+    Transformation {
+        output: regroup(&sizes, transformation.output),
+        history: transformation
+            .history
+            .into_iter()
+            .map(|history| {
+                let mut position = 0;
+                let blocked = sizes
+                    .iter()
+                    .map(|size| {
+                        position += size;
+                        history.blocked[position - size..position]
+                            .iter()
+                            .any(|blocked| *blocked)
+                    })
+                    .collect();
+                History {
+                    words: regroup(&sizes, history.words),
+                    blocked,
+                    ..history
+                }
+            })
+            .collect(),
+    }
+}
+
+fn regroup(sizes: &[usize], variants: Vec<Vec<String>>) -> Vec<Vec<String>> {
+    let mut variants = variants.into_iter();
+    sizes
+        .iter()
+        .map(|size| {
+            let mut result: Vec<String> = vec![];
+            variants.by_ref().take(*size).flatten().for_each(|word| {
+                if !result.contains(&word) {
+                    result.push(word)
+                }
+            });
+            result
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn branch(input: &'static str) -> AST<'static> {
+        crate::wgl::from_string(input).unwrap()
+    }
+
+    #[test]
+    fn test_cognates() {
+        let proto = crate::sound_system::from_string("letters: a, i, p, t, b, v\n").unwrap();
+        let mut family = Family::new("Proto", proto);
+        family
+            .add_branch("West", None, branch("rules:\nV_V: p -> b\n"))
+            .unwrap();
+        family
+            .add_branch("Far West", Some("west"), branch("rules:\nV_V: b -> v\n"))
+            .unwrap();
+        family
+            .add_branch("East", None, branch("rules:\n_#: a -> i\n"))
+            .unwrap();
+        assert_eq!(
+            family.add_branch("North", Some("Center"), branch("rules:\n_: a -> i\n")),
+            Err("unknown parent language")
+        );
+        assert_eq!(
+            family.add_branch("East", None, branch("rules:\n_: a -> i\n")),
+            Err("duplicate language")
+        );
+        let words = family
+            .cognates(vec!["apa".to_string(), "pata".to_string()])
+            .into_iter()
+            .map(|transformation| transformation.output)
+            .collect::<Vec<_>>();
+        let expected = [
+            ["apa", "pata"],
+            ["aba", "pata"],
+            ["ava", "pata"],
+            ["api", "pati"],
+        ];
+        assert_eq!(
+            words,
+            expected
+                .iter()
+                .map(|words| words.iter().map(|word| vec![word.to_string()]).collect())
+                .collect::<Vec<Vec<Vec<String>>>>()
+        );
+    }
+
+    #[test]
+    fn test_family_definition() {
+        let family = crate::wgl::family_from_string(
+            "proto \"Proto-Lath\": lath.wgl\nbranch West: west.wgl\n\
+             branch \"Far West\" from West: west/far.wgl\n",
+        )
+        .unwrap();
+        assert_eq!(family.proto, ("Proto-Lath", "lath.wgl"));
+        assert_eq!(
+            family.branches,
+            vec![
+                ("West", None, "west.wgl"),
+                ("Far West", Some("West"), "west/far.wgl")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_regroup() {
+        let variants = vec![
+            vec!["a".to_string()],
+            vec!["b".to_string()],
+            vec!["a".to_string()],
+            vec!["c".to_string()],
+        ];
+        assert_eq!(
+            regroup(&[3, 1], variants),
+            vec![
+                vec!["a".to_string(), "b".to_string()],
+                vec!["c".to_string()]
+            ]
+        );
+    }
+}
@@ -1,9 +1,12 @@
 use super::distribution;
-use super::{Distribution, MonoSyllableRepartition, SoundSystem};
+use super::{Distribution, MonoSyllableRepartition, SoundSystem, Syllable};
 use rand::prelude::*;
 use std::collections::HashMap;
 use std::str::FromStr;
 
+/// Attempts to find a syllable agreeing with the vowel harmony.
+const ATTEMPTS: usize = 20;
+
 impl MonoSyllableRepartition {
     pub fn into_percentage(self) -> f32 {
         match self {
@@ -41,16 +44,12 @@ impl SoundSystem {
     ) -> Vec<String> {
         let mut words = vec![];
         let mut distribution_cache: HashMap<String, Vec<Distribution>> = HashMap::new();
-        let percentage = repartition.into_percentage();
         for _ in 0..number {
-            let mut number_of_syllables = 1;
-            if percentage < 1.0 && random::<f32>() > percentage {
-                number_of_syllables += 1 + distribution::power_law(4, 0.5);
-            }
-            let mut word = String::new();
-            for _ in 0..number_of_syllables {
-                word.push_str(&self.syllable(&mut distribution_cache));
-            }
+            let word = self.word(
+                self.syllables(),
+                number_of_syllables(repartition),
+                &mut distribution_cache,
+            );
             if !words.contains(&word) {
                 words.push(word);
             }
@@ -58,12 +57,58 @@ impl SoundSystem {
         words
     }
 
-    fn syllable(&self, distribution_cache: &mut HashMap<String, Vec<Distribution>>) -> String {
-        let syllables_size = self.syllables().len();
+    /// Word of `number_of_syllables` syllables following `patterns`,
+    /// each syllable taking a tone.
+    pub(super) fn word(
+        &self,
+        patterns: &[Syllable],
+        number_of_syllables: usize,
+        distribution_cache: &mut HashMap<String, Vec<Distribution>>,
+    ) -> String {
+        let mut word = String::new();
+        let mut harmony = vec![None; self.harmonies().len()];
+        for _ in 0..number_of_syllables {
+            let mut syllable = None;
+            for _ in 0..ATTEMPTS {
+                let mut state = harmony.clone();
+                if let Some(harmonic) =
+                    self.syllable(patterns, distribution_cache, &mut state, true)
+                {
+                    syllable = Some(harmonic);
+                    harmony = state;
+                    break;
+                }
+            }
+            let syllable = syllable.unwrap_or_else(|| {
+                warn!("No syllable agrees with the vowel harmony of {}", word);
+                self.syllable(patterns, distribution_cache, &mut harmony, false)
+                    .unwrap_or_default()
+            });
+            word.push_str(&syllable);
+            if let Some(toneme) = self.random_tone() {
+                word.push_str(&toneme.mark);
+            }
+        }
+        word
+    }
+
+    /// Syllable following one of `patterns`, none when a letter has to agree
+    /// with the vowel harmony and no letter of its class does.
+    fn syllable(
+        &self,
+        patterns: &[Syllable],
+        distribution_cache: &mut HashMap<String, Vec<Distribution>>,
+        harmony: &mut [Option<usize>],
+        agree: bool,
+    ) -> Option<String> {
+        let syllables_size = patterns.len();
+        if syllables_size == 0 {
+            return Some(String::new());
+        }
         let syllable_drop = syllable_drop(syllables_size);
         let mut syllable = String::new();
         let index = distribution::power_law(syllables_size, syllable_drop);
-        let pattern = &self.syllables()[index];
+        let pattern = &patterns[index];
         for name in pattern {
             let class_name = name.trim_end_matches('?');
             let distribution = distribution_cache
@@ -80,13 +125,31 @@ impl SoundSystem {
             if !distribution.is_empty() {
                 let is_random = name.ends_with('?');
                 if !is_random || rand::random() {
-                    let letter = distribution::select(distribution);
+                    let mut candidates = self.harmonic(distribution, harmony);
+                    if candidates.is_empty() {
+                        if agree {
+                            return None;
+                        }
+                        candidates = distribution.clone();
+                    }
+                    let letter = distribution::select(&candidates);
+                    self.update_harmony(letter, harmony);
                     syllable.push_str(letter);
                 }
             }
         }
-        syllable
+        Some(syllable)
+    }
+}
+
+/// Number of syllables of a word, one syllable being as frequent as `repartition`.
+pub(super) fn number_of_syllables(repartition: MonoSyllableRepartition) -> usize {
+    let percentage = repartition.into_percentage();
+    let mut number_of_syllables = 1;
+    if percentage < 1.0 && random::<f32>() > percentage {
+        number_of_syllables += 1 + distribution::power_law(4, 0.5);
     }
+    number_of_syllables
 }
 
 fn syllable_drop(number_of_syllables: usize) -> f32 {
@@ -0,0 +1,143 @@
+use super::phone::Phone;
+use super::{Distribution, Harmony, HarmonyKind, SoundSystem};
+use std::str::FromStr;
+
+impl FromStr for HarmonyKind {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "backness" => Ok(HarmonyKind::Backness),
+            "roundness" => Ok(HarmonyKind::Roundness),
+            "height" => Ok(HarmonyKind::Height),
+            _ => Err("no match"),
+        }
+    }
+}
+
+impl SoundSystem {
+    /// Whether all the vowels of `word` agree for every harmony.
+    pub fn is_harmonic(&self, word: &'_ str) -> bool {
+        let mut state = vec![None; self.harmonies().len()];
+        self.segments(word)
+            .iter()
+            .all(|(letter, _)| self.update_harmony(letter, &mut state))
+    }
+
+    /// Letters of `distribution` agreeing with the vowels already chosen.
+    pub(super) fn harmonic(
+        &self,
+        distribution: &[Distribution],
+        state: &[Option<usize>],
+    ) -> Vec<Distribution> {
+        distribution
+            .iter()
+            .filter(|(letter, _)| {
+                self.harmonies()
+                    .iter()
+                    .zip(state)
+                    .all(|(harmony, current)| agrees(*current, self.harmony_key(harmony, letter)))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Records the harmonic sets of `letter`, returning if it agrees
+    /// with the previous vowels.
+    pub(super) fn update_harmony(&self, letter: &'_ str, state: &mut [Option<usize>]) -> bool {
+        self.harmonies()
+            .iter()
+            .zip(state.iter_mut())
+            .fold(true, |agree, (harmony, current)| {
+                let key = self.harmony_key(harmony, letter);
+                let result = agrees(*current, key);
+                if current.is_none() {
+                    *current = key;
+                }
+                agree && result
+            })
+    }
+
+    /// Set of `letter` for `harmony`, `None` for consonants and neutral letters.
+    fn harmony_key(&self, harmony: &Harmony, letter: &'_ str) -> Option<usize> {
+        if harmony.neutral.iter().any(|neutral| neutral == letter) {
+            return None;
+        }
+        let vowel = self
+            .phones(letter)
+            .into_iter()
+            .find_map(|phone| match phone {
+                Phone::Vowel(vowel) => Some(vowel),
+                _ => None,
+            })?;
+        match &harmony.kind {
+            HarmonyKind::Classes(names) => names.iter().position(|name| {
+                self.classes()
+                    .get(name)
+                    .map(|letters| letters.iter().any(|l| l == letter))
+                    .unwrap_or(false)
+            }),
+            HarmonyKind::Backness => Some(vowel.backness as usize),
+            HarmonyKind::Roundness => Some(vowel.roundness as usize),
+            HarmonyKind::Height => Some(vowel.height as usize),
+        }
+    }
+}
+
+fn agrees(current: Option<usize>, key: Option<usize>) -> bool {
+    match (current, key) {
+        (Some(current), Some(key)) => current == key,
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sound_system::MonoSyllableRepartition;
+
+    const INPUT: &str = "letters: a, e, i, o, u, y, k, t, m
+syllables: CV CVC
+F = e i y
+B = a o u
+harmony: F B neutral i
+";
+
+    #[test]
+    fn test_is_harmonic() {
+        let sound_system = crate::sound_system::from_string(INPUT).unwrap();
+        assert!(sound_system.is_harmonic("kemity"));
+        assert!(sound_system.is_harmonic("katimo"));
+        assert!(!sound_system.is_harmonic("kemato"));
+    }
+
+    #[test]
+    fn test_generate_harmonic_words() {
+        let sound_system = crate::sound_system::from_string(INPUT).unwrap();
+        sound_system
+            .generate_words(50, MonoSyllableRepartition::Never)
+            .iter()
+            .for_each(|word| assert!(sound_system.is_harmonic(word), "{}", word));
+    }
+
+    #[test]
+    fn test_retry_disharmonic_syllables() {
+        let sound_system = crate::sound_system::from_string(
+            "letters: a, e, i, o, u, k, t\nsyllables: CV CA\nV = a e i o u\nA = a\n\
+             F = e i\nB = a o u\nharmony: F B\n",
+        )
+        .unwrap();
+        sound_system
+            .generate_words(50, MonoSyllableRepartition::Never)
+            .iter()
+            .for_each(|word| assert!(sound_system.is_harmonic(word), "{}", word));
+    }
+
+    #[test]
+    fn test_feature_harmony() {
+        let sound_system =
+            crate::sound_system::from_string("letters: a, e, o, y, k\nharmony: roundness\n")
+                .unwrap();
+        assert!(sound_system.is_harmonic("koky"));
+        assert!(!sound_system.is_harmonic("koka"));
+    }
+}
@@ -0,0 +1,341 @@
+use super::compound::MORPHEME_BOUNDARY;
+use super::phrase::WORD_BOUNDARY;
+use super::{Pattern, SoundSystem};
+use std::collections::HashMap;
+use std::ops::Range;
+
+/// Name of the capture of the input of a rule.
+pub(super) const INPUT: &str = "input";
+/// Name of the captures of the syllable boundaries `$` matched.
+pub(super) const SYLLABLE: &str = "$";
+
+/// Captures by name with the letters they cover, in the order of the pattern.
+pub(super) type Captures = Vec<(String, Range<usize>)>;
+
+/// A word split into its letters, a digraph being a single letter,
+/// with the letters starting a syllable when the word is syllabified.
+pub(super) struct Segments<'a> {
+    word: &'a str,
+    bounds: Vec<Range<usize>>,
+    syllables: Vec<usize>,
+}
+
+/// A match of a pattern, as letter positions.
+pub(super) struct Found {
+    pub range: Range<usize>,
+    pub captures: Captures,
+}
+
+impl Found {
+    /// Letters of the input of the rule.
+    pub fn input(&self) -> Range<usize> {
+        self.captures
+            .iter()
+            .find(|(name, _)| name == INPUT)
+            .map(|(_, range)| range.clone())
+            .unwrap_or_else(|| self.range.clone())
+    }
+}
+
+impl SoundSystem {
+    /// Letters and class members a word is split into, the longest first.
+    pub(super) fn segment_letters(&self) -> Vec<String> {
+        let phonemes = self
+            .phonemes_sorted()
+            .iter()
+            .map(|(letter, _)| letter.clone())
+            .collect::<Vec<_>>();
+        segment_letters(phonemes, self.classes())
+    }
+}
+
+pub(super) fn segment_letters(
+    letters: Vec<String>,
+    classes: &HashMap<String, Vec<String>>,
+) -> Vec<String> {
+    let mut letters = letters;
+    classes.values().flatten().for_each(|letter| {
+        if !letters.contains(letter) {
+            letters.push(letter.clone())
+        }
+    });
+    letters.sort_by(|left, right| right.len().cmp(&left.len()).then(left.cmp(right)));
+    letters
+}
+
+impl<'a> Segments<'a> {
+    pub fn new(word: &'a str, letters: &[String]) -> Self {
+        let mut bounds = vec![];
+        let mut position = 0;
+        while let Some(c) = word[position..].chars().next() {
+            let length = letters
+                .iter()
+                .find(|letter| !letter.is_empty() && word[position..].starts_with(letter.as_str()))
+                .map(String::len)
+                .unwrap_or_else(|| c.len_utf8());
+            bounds.push(position..position + length);
+            position += length;
+        }
+        Segments {
+            word,
+            bounds,
+            syllables: vec![],
+        }
+    }
+
+    /// Marks the syllables starting at the bytes `boundaries`, matched by `$`.
+    pub fn with_boundaries(mut self, boundaries: &[usize]) -> Self {
+        self.syllables = boundaries
+            .iter()
+            .map(|boundary| self.index(*boundary))
+            .collect();
+        self
+    }
+
+    pub fn len(&self) -> usize {
+        self.bounds.len()
+    }
+
+    pub fn get(&self, index: usize) -> Option<&'a str> {
+        self.bounds
+            .get(index)
+            .map(|bounds| &self.word[bounds.clone()])
+    }
+
+    /// Index of the first letter starting at or after the byte `position`.
+    pub fn index(&self, position: usize) -> usize {
+        self.bounds
+            .iter()
+            .position(|bounds| bounds.start >= position)
+            .unwrap_or_else(|| self.len())
+    }
+
+    /// Bytes of a range of letters.
+    pub fn bytes(&self, range: &Range<usize>) -> Range<usize> {
+        let offset = |index: usize| {
+            self.bounds
+                .get(index)
+                .map(|bounds| bounds.start)
+                .unwrap_or_else(|| self.word.len())
+        };
+        offset(range.start)..offset(range.end)
+    }
+
+    /// Letters of the word in a range.
+    pub fn text(&self, range: &Range<usize>) -> &'a str {
+        &self.word[self.bytes(range)]
+    }
+}
+
+/// Name of the capture of a class: `C` or `C1`, followed by
+/// its occurrence in the rule after the first one.
+pub(super) fn group_name(name: &'_ str, occurrence: usize) -> String {
+    if occurrence > 1 {
+        format!("{}_{}", name, occurrence)
+    } else {
+        name.to_string()
+    }
+}
+
+/// Class and index of a capture.
+pub(super) fn group_class(name: &'_ str) -> (char, Option<&str>) {
+    let mut chars = name.chars();
+    let class = chars.next().unwrap_or_default();
+    let index = chars.as_str().split('_').next().unwrap_or_default();
+    (class, if index.is_empty() { None } else { Some(index) })
+}
+
+/// First match starting at the letter `from` or after it accepted by `accept`.
+pub(super) fn find<F>(
+    patterns: &[Pattern],
+    segments: &Segments<'_>,
+    from: usize,
+    mut accept: F,
+) -> Option<Found>
+where
+    F: FnMut(&Found) -> bool,
+{
+    (from..=segments.len()).find_map(|start| {
+        let mut state = State {
+            segments,
+            captures: vec![],
+        };
+        let mut result = None;
+        let mut done = |state: &mut State<'_, '_>, end: usize| {
+            let found = Found {
+                range: start..end,
+                captures: state.captures.clone(),
+            };
+            let accepted = accept(&found);
+            if accepted {
+                result = Some(found);
+            }
+            accepted
+        };
+        matches(&mut state, patterns, start, &mut done);
+        result
+    })
+}
+
+struct State<'s, 'a> {
+    segments: &'s Segments<'a>,
+    captures: Captures,
+}
+
+impl<'s, 'a> State<'s, 'a> {
+    /// An indexed class captured several times is always the same letter.
+    fn is_consistent(&self, name: &'_ str, letter: &'_ str) -> bool {
+        match group_class(name) {
+            (_, None) => true,
+            key => self
+                .captures
+                .iter()
+                .filter(|(other, _)| group_class(other) == key)
+                .all(|(_, range)| self.segments.text(range) == letter),
+        }
+    }
+}
+
+type Next<'n, 's, 'a> = dyn FnMut(&mut State<'s, 'a>, usize) -> bool + 'n;
+
+/// Backtracks over the patterns, `next` receives the position after them.
+fn matches<'s, 'a>(
+    state: &mut State<'s, 'a>,
+    patterns: &[Pattern],
+    position: usize,
+    next: &mut Next<'_, 's, 'a>,
+) -> bool {
+    let (first, rest) = match patterns.split_first() {
+        Some(split) => split,
+        None => return next(state, position),
+    };
+    let length = state.segments.len();
+    let letter = state.segments.get(position);
+    match first {
+        Pattern::Start => {
+            let boundary = WORD_BOUNDARY.to_string();
+            (position == 0 || state.segments.get(position - 1) == Some(boundary.as_str()))
+                && matches(state, rest, position, next)
+        }
+        Pattern::End => {
+            let boundary = WORD_BOUNDARY.to_string();
+            (position == length || letter == Some(boundary.as_str()))
+                && matches(state, rest, position, next)
+        }
+        Pattern::WordBoundary => {
+            let boundary = WORD_BOUNDARY.to_string();
+            letter == Some(boundary.as_str()) && matches(state, rest, position + 1, next)
+        }
+        Pattern::SyllableBoundary => {
+            if position != 0 && position != length && !state.segments.syllables.contains(&position)
+            {
+                return false;
+            }
+            state
+                .captures
+                .push((SYLLABLE.to_string(), position..position));
+            let matched = matches(state, rest, position, next);
+            state.captures.pop();
+            matched
+        }
+        Pattern::MorphemeBoundary => {
+            let boundary = MORPHEME_BOUNDARY.to_string();
+            letter == Some(boundary.as_str()) && matches(state, rest, position + 1, next)
+        }
+        Pattern::Letter(expected) => {
+            letter == Some(expected.as_str()) && matches(state, rest, position + 1, next)
+        }
+        Pattern::Letters(letters, capture) => match letter {
+            Some(letter) if letters.iter().any(|l| l == letter) => match capture {
+                None => matches(state, rest, position + 1, next),
+                Some(name) if state.is_consistent(name, letter) => {
+                    state.captures.push((name.clone(), position..position + 1));
+                    let matched = matches(state, rest, position + 1, next);
+                    state.captures.pop();
+                    matched
+                }
+                Some(_) => false,
+            },
+            _ => false,
+        },
+        Pattern::Alternation(alternatives) => alternatives.iter().any(|alternative| {
+            let mut then = |state: &mut State<'s, 'a>, end: usize| matches(state, rest, end, next);
+            matches(state, alternative, position, &mut then)
+        }),
+        Pattern::Optional(optional) => {
+            let mut then = |state: &mut State<'s, 'a>, end: usize| matches(state, rest, end, next);
+            matches(state, optional, position, &mut then) || matches(state, rest, position, next)
+        }
+        Pattern::Repeat(repeated) => {
+            let mut then = |state: &mut State<'s, 'a>, end: usize| {
+                let mut after =
+                    |state: &mut State<'s, 'a>, end: usize| matches(state, rest, end, next);
+                let again =
+                    end > position && matches(state, std::slice::from_ref(first), end, &mut after);
+                again || matches(state, rest, end, next)
+            };
+            matches(state, repeated, position, &mut then)
+        }
+        Pattern::Gap => (position..=length).any(|end| matches(state, rest, end, next)),
+        Pattern::Input(input) => {
+            let mut then = |state: &mut State<'s, 'a>, end: usize| {
+                state.captures.push((INPUT.to_string(), position..end));
+                let matched = matches(state, rest, end, next);
+                state.captures.pop();
+                matched
+            };
+            matches(state, input, position, &mut then)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn apply(input: &str, word: &str) -> String {
+        let mut sound_system = crate::sound_system::from_string(input).unwrap();
+        sound_system
+            .sound_trasformation(vec![word.to_string()])
+            .output
+            .remove(0)
+            .remove(0)
+    }
+
+    #[test]
+    fn test_segments() {
+        let letters = vec!["th".to_string(), "a".to_string(), "t".to_string()];
+        let segments = Segments::new("thata", &letters);
+        assert_eq!(
+            (0..segments.len())
+                .filter_map(|index| segments.get(index))
+                .collect::<Vec<_>>(),
+            vec!["th", "a", "t", "a"]
+        );
+        assert_eq!(segments.index(1), 1);
+        assert_eq!(segments.bytes(&(1..3)), 2..4);
+    }
+
+    #[test]
+    fn test_digraphs() {
+        let input = "letters: a, e, t, th, d\nrules:\n_: t -> d\n";
+        assert_eq!(apply(input, "thata"), "thada");
+        let input = "letters: a, e, t, th\nrules:\n_t: a -> e\n";
+        assert_eq!(apply(input, "atha"), "atha");
+        assert_eq!(apply(input, "ata"), "eta");
+        let input = "letters: a, e, t, th\nrules:\n_th: a -> e\n";
+        assert_eq!(apply(input, "atha"), "etha");
+    }
+
+    #[test]
+    fn test_literals() {
+        let input = "letters: a, e, |, +\nphonemes:\n| /h/\n+ /ʔ/\nrules:\n_: | -> +\n";
+        assert_eq!(apply(input, "a|a"), "a+a");
+        let input = "letters: a, e, |\nphonemes:\n| /h/\nrules:\n|_: a -> e\n";
+        assert_eq!(apply(input, "|aa"), "|ea");
+        let input = "letters: a, e, (\nphonemes:\n( /ʔ/\nrules:\n_: ( -> e\n";
+        assert_eq!(apply(input, "a(a"), "aea");
+        let input = "letters: a, e, .\nphonemes:\n. /ʔ/\nrules:\n_: a. -> e\n";
+        assert_eq!(apply(input, "aa.a"), "aea");
+    }
+}
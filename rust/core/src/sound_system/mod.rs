@@ -1,14 +1,37 @@
 use crate::wgl;
 use pest::error::Error;
 use std::fmt;
+mod adaptation;
 mod compiler;
+mod compound;
 mod distribution;
+mod family;
 mod generator;
+mod harmony;
+mod matcher;
+mod morphology;
+mod names;
+mod orthography;
+mod phrase;
+mod reconstruction;
+mod reduplication;
+mod roots;
+mod similarity;
+mod stress;
+mod syllable;
+mod tone;
 mod types;
 
 pub mod phone;
 pub mod rules;
+pub use adaptation::{Adaptation, AdaptationStep};
 pub use compiler::*;
+pub use compound::{compound, MORPHEME_BOUNDARY};
+pub use family::{Family, Language};
+pub use morphology::Inflection;
+pub use phrase::WORD_BOUNDARY;
+pub use reconstruction::Reconstruction;
+pub use roots::Derivation;
 pub use types::*;
 
 pub fn from_string(input: &'_ str) -> Result<SoundSystem, Error<wgl::Rule>> {
@@ -60,7 +83,55 @@ impl SoundSystem {
     }
 
     pub fn ipa_representation(&self, word: &'_ str) -> String {
+        self.phonetic_representation(word, phone::Transcription::Ipa)
+    }
+
+    /// Syllables are separated by the transcription separator,
+    /// or by their stress mark when the word has several syllables.
+    /// Tones follow the nucleus of their syllable.
+    pub fn phonetic_representation(
+        &self,
+        word: &'_ str,
+        transcription: phone::Transcription,
+    ) -> String {
+        let syllables = self.prosodic_syllables(word);
+        let marked = syllables.len() > 1;
         let mut result = String::new();
+        syllables
+            .iter()
+            .enumerate()
+            .for_each(|(index, (syllable, stress, tone))| {
+                match stress.phone().filter(|_| marked) {
+                    Some(mark) => {
+                        result.push_str(&transcription.representation(self.inventory(), &[mark]))
+                    }
+                    None if index > 0 => result.push_str(transcription.syllable_separator()),
+                    None => (),
+                }
+                let (onset, nucleus, coda) = syllable::syllable_parts(syllable);
+                let phones = onset
+                    .iter()
+                    .chain(nucleus.iter())
+                    .flat_map(|(_, phones)| phones.iter().copied())
+                    .chain(tone.iter().flat_map(|toneme| toneme.phones.iter().copied()))
+                    .chain(coda.iter().flat_map(|(_, phones)| phones.iter().copied()))
+                    .collect::<Vec<_>>();
+                result.push_str(&transcription.representation(self.inventory(), &phones));
+            });
+        result
+    }
+
+    pub fn phones(&self, word: &'_ str) -> phone::Phones {
+        self.segments(word)
+            .into_iter()
+            .flat_map(|(_, phones)| phones)
+            .collect()
+    }
+
+    /// Splits a word into its letters, with the phones of each one.
+    /// Unknown characters are kept without phones.
+    pub fn segments(&self, word: &'_ str) -> Vec<Segment> {
+        let mut result = vec![];
         let phonemes = self.phonemes_sorted();
         let mut input = word.to_string();
         let mut position: usize = 0;
@@ -71,13 +142,13 @@ impl SoundSystem {
                 .find_map(|tuple| self.find_phoneme(&input, tuple, position, length))
             {
                 Some((letter, phones)) => {
-                    phones
-                        .iter()
-                        .map(|p| p.clone().into())
-                        .for_each(|c| result.push(c));
+                    result.push((letter.to_string(), phones.clone()));
                     letter.chars().count()
                 }
-                None => 1,
+                None => {
+                    result.push((input.chars().take(1).collect(), vec![]));
+                    1
+                }
             };
             position += skip;
             input = input.chars().skip(skip).collect::<String>();
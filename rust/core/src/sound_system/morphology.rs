@@ -0,0 +1,340 @@
+use super::phone::Phone;
+use super::{AffixCondition, Allomorph, Paradigm, SoundSystem};
+
+/// Form of a stem in a cell of a paradigm.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Inflection {
+    /// Gloss of each dimension.
+    pub cell: Vec<String>,
+    /// Morphemes separated by `-`, an infix between `<` and `>`,
+    /// a reduplicant joined with `~`.
+    pub segmented: String,
+    /// Stem with the glosses of its affixes, such as `kat-ACC-PL`.
+    pub gloss: String,
+    /// Word before the rules.
+    pub word: String,
+    /// Variants of the word after the rules.
+    pub forms: Vec<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum MorphKind {
+    Stem,
+    Prefix,
+    Infix,
+    Suffix,
+    Reduplicant,
+}
+
+#[derive(Debug)]
+struct Morph {
+    letters: String,
+    gloss: String,
+    kind: MorphKind,
+}
+
+impl Paradigm {
+    /// Glosses of every cell, the last dimension changing first.
+    pub fn cells(&self) -> Vec<Vec<String>> {
+        self.dimensions
+            .iter()
+            .fold(vec![vec![]], |cells, dimension| {
+                cells
+                    .iter()
+                    .flat_map(|cell| {
+                        dimension.glosses.iter().map(move |gloss| {
+                            let mut cell = cell.clone();
+                            cell.push(gloss.clone());
+                            cell
+                        })
+                    })
+                    .collect()
+            })
+    }
+}
+
+impl SoundSystem {
+    pub fn paradigm(&self, name: &'_ str) -> Option<&Paradigm> {
+        self.paradigms()
+            .iter()
+            .find(|paradigm| paradigm.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Every cell of the paradigm for `stem`, each form going through the rules
+    /// so that the alternations at the morpheme boundaries appear.
+    pub fn inflect_paradigm(
+        &mut self,
+        name: &'_ str,
+        stem: &'_ str,
+    ) -> Result<Vec<Inflection>, &'static str> {
+        let cells = self.paradigm(name).ok_or("unknown paradigm")?.cells();
+        let mut inflections = cells
+            .iter()
+            .map(|cell| self.inflect(stem, cell))
+            .collect::<Vec<_>>();
+        let transformation = self.sound_trasformation(
+            inflections
+                .iter()
+                .map(|inflection| inflection.word.clone())
+                .collect(),
+        );
+        inflections
+            .iter_mut()
+            .zip(transformation.output)
+            .for_each(|(inflection, forms)| inflection.forms = forms);
+        Ok(inflections)
+    }
+
+    /// Adds the affixes of `glosses` to `stem` one after the other, the first one
+    /// being the nearest to the stem. A gloss without affix is a zero morpheme.
+    /// The forms are left empty, the word did not go through the rules.
+    pub fn inflect(&self, stem: &'_ str, glosses: &[String]) -> Inflection {
+        let mut morphs = vec![Morph {
+            letters: stem.to_string(),
+            gloss: stem.to_string(),
+            kind: MorphKind::Stem,
+        }];
+        glosses.iter().for_each(|gloss| {
+            match self.affixes().iter().find(|affix| &affix.gloss == gloss) {
+                Some(affix) => match affix
+                    .allomorphs
+                    .iter()
+                    .find(|allomorph| self.fits(&morphs, allomorph))
+                {
+                    Some(allomorph) => self.add_allomorph(&mut morphs, gloss, allomorph),
+                    None => warn!("No allomorph of {} fits {}", gloss, letters(&morphs)),
+                },
+                None => morphs.push(Morph {
+                    letters: String::new(),
+                    gloss: gloss.clone(),
+                    kind: MorphKind::Suffix,
+                }),
+            }
+        });
+        Inflection {
+            cell: glosses.to_vec(),
+            segmented: render(&morphs, |morph| &morph.letters),
+            gloss: render(&morphs, |morph| &morph.gloss),
+            word: letters(&morphs),
+            forms: vec![],
+        }
+    }
+
+    /// Whether the letters around where the allomorph goes meet its condition.
+    fn fits(&self, morphs: &[Morph], allomorph: &Allomorph) -> bool {
+        let word = letters(morphs);
+        let segments = self.segments(&word);
+        let (preceding, following) = if let Some(reduplication) = &allomorph.reduplication {
+            if self.reduplicant(&word, reduplication).is_none() {
+                return false;
+            }
+            if reduplication.prefixed {
+                (None, segments.first().map(|(letter, _)| letter))
+            } else {
+                (segments.last().map(|(letter, _)| letter), None)
+            }
+        } else if !allomorph.infix.is_empty() {
+            let (index, offset) = match self.infix_site(morphs) {
+                Some(site) => site,
+                None => return false,
+            };
+            let position = morphs[..index]
+                .iter()
+                .map(|morph| morph.letters.len())
+                .sum::<usize>()
+                + offset;
+            let mut start = 0;
+            let mut preceding = None;
+            let mut following = None;
+            segments.iter().for_each(|(letter, _)| {
+                if start < position {
+                    preceding = Some(letter);
+                } else if following.is_none() {
+                    following = Some(letter);
+                }
+                start += letter.len();
+            });
+            (preceding, following)
+        } else if allomorph.suffix.is_empty() && !allomorph.prefix.is_empty() {
+            (None, segments.first().map(|(letter, _)| letter))
+        } else if allomorph.prefix.is_empty() {
+            (segments.last().map(|(letter, _)| letter), None)
+        } else {
+            (
+                segments.last().map(|(letter, _)| letter),
+                segments.first().map(|(letter, _)| letter),
+            )
+        };
+        match &allomorph.condition {
+            AffixCondition::Always => true,
+            AffixCondition::After(letters) => preceding
+                .map(|letter| letters.contains(letter))
+                .unwrap_or(false),
+            AffixCondition::Before(letters) => following
+                .map(|letter| letters.contains(letter))
+                .unwrap_or(false),
+        }
+    }
+
+    /// Position of the first vowel of the stem: the morph and the offset in its letters.
+    fn infix_site(&self, morphs: &[Morph]) -> Option<(usize, usize)> {
+        morphs
+            .iter()
+            .enumerate()
+            .filter(|(_, morph)| morph.kind == MorphKind::Stem)
+            .find_map(|(index, morph)| {
+                let mut offset = 0;
+                self.segments(&morph.letters)
+                    .iter()
+                    .find_map(|(letter, phones)| {
+                        if let Some(Phone::Vowel(_)) = phones.first() {
+                            return Some(offset);
+                        }
+                        offset += letter.len();
+                        None
+                    })
+                    .map(|offset| (index, offset))
+            })
+    }
+
+    fn add_allomorph(&self, morphs: &mut Vec<Morph>, gloss: &'_ str, allomorph: &Allomorph) {
+        let morph = |letters: &String, kind| Morph {
+            letters: letters.clone(),
+            gloss: gloss.to_string(),
+            kind,
+        };
+        if let Some(reduplication) = &allomorph.reduplication {
+            if let Some(copy) = self.reduplicant(&letters(morphs), reduplication) {
+                if reduplication.prefixed {
+                    morphs.insert(0, morph(&copy, MorphKind::Reduplicant));
+                } else {
+                    morphs.push(morph(&copy, MorphKind::Reduplicant));
+                }
+            }
+            return;
+        }
+        if !allomorph.infix.is_empty() {
+            if let Some((index, offset)) = self.infix_site(morphs) {
+                let stem = morphs.remove(index);
+                let (start, end) = stem.letters.split_at(offset);
+                let mut split = vec![];
+                if !start.is_empty() {
+                    split.push(Morph {
+                        letters: start.to_string(),
+                        gloss: start.to_string(),
+                        kind: MorphKind::Stem,
+                    });
+                }
+                split.push(morph(&allomorph.infix, MorphKind::Infix));
+                split.push(Morph {
+                    letters: end.to_string(),
+                    gloss: end.to_string(),
+                    kind: MorphKind::Stem,
+                });
+                morphs.splice(index..index, split);
+            }
+        }
+        if !allomorph.prefix.is_empty() {
+            morphs.insert(0, morph(&allomorph.prefix, MorphKind::Prefix));
+        }
+        if !allomorph.suffix.is_empty() || allomorph.prefix.is_empty() && allomorph.infix.is_empty()
+        {
+            morphs.push(morph(&allomorph.suffix, MorphKind::Suffix));
+        }
+    }
+}
+
+fn letters(morphs: &[Morph]) -> String {
+    morphs.iter().map(|morph| morph.letters.as_str()).collect()
+}
+
+/// Morphs separated by `-`, an infix being written between `<` and `>`
+/// and a reduplicant joined with `~`. Empty morphs are skipped.
+fn render(morphs: &[Morph], text: impl Fn(&Morph) -> &String) -> String {
+    let mut result = String::new();
+    let mut previous = None;
+    morphs.iter().for_each(|morph| {
+        let text = text(morph);
+        if text.is_empty() {
+            return;
+        }
+        match (previous, morph.kind) {
+            (None, _) | (_, MorphKind::Infix) | (Some(MorphKind::Infix), _) => {}
+            (_, MorphKind::Reduplicant) | (Some(MorphKind::Reduplicant), _) => result.push('~'),
+            _ => result.push('-'),
+        }
+        if morph.kind == MorphKind::Infix {
+            result.push('<');
+            result.push_str(text);
+            result.push('>');
+        } else {
+            result.push_str(text);
+        }
+        previous = Some(morph.kind);
+    });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sound_system() -> SoundSystem {
+        crate::sound_system::from_string(
+            "letters: a, i, e, u, p, t, d, k, m, l\n\
+             morphology:\n PL: -i\n ACC: -m after V, -em\n DIM: ta-\n PROG: <al>\n NEG: ka- -t\n INT: [CV]-\n\
+             paradigm noun:\n case = NOM ACC\n number = SG PL\n\
+             rules:\nV_V: t -> d\n",
+        )
+        .unwrap()
+    }
+
+    fn glosses(glosses: &[&str]) -> Vec<String> {
+        glosses.iter().map(|gloss| gloss.to_string()).collect()
+    }
+
+    #[test]
+    fn test_affixes() {
+        let sound_system = sound_system();
+        let inflection = sound_system.inflect("kat", &glosses(&["ACC", "PL"]));
+        assert_eq!(inflection.segmented, "kat-em-i");
+        assert_eq!(inflection.gloss, "kat-ACC-PL");
+        assert_eq!(inflection.word, "katemi");
+        assert_eq!(
+            sound_system.inflect("pika", &glosses(&["ACC"])).segmented,
+            "pika-m"
+        );
+        let inflection = sound_system.inflect("kat", &glosses(&["DIM", "PROG"]));
+        assert_eq!(inflection.segmented, "ta-k<al>at");
+        assert_eq!(inflection.gloss, "DIM-k<PROG>at");
+        let inflection = sound_system.inflect("pum", &glosses(&["NEG"]));
+        assert_eq!(inflection.segmented, "ka-pum-t");
+        assert_eq!(inflection.gloss, "NEG-pum-NEG");
+        let inflection = sound_system.inflect("kata", &glosses(&["INT"]));
+        assert_eq!(inflection.segmented, "ka~kata");
+        assert_eq!(inflection.gloss, "INT~kata");
+        assert_eq!(inflection.word, "kakata");
+    }
+
+    #[test]
+    fn test_paradigm() {
+        let mut sound_system = sound_system();
+        let inflections = sound_system.inflect_paradigm("noun", "kat").unwrap();
+        assert_eq!(
+            inflections
+                .iter()
+                .map(|inflection| (inflection.gloss.as_str(), inflection.forms.clone()))
+                .collect::<Vec<_>>(),
+            vec![
+                ("kat-NOM-SG", vec!["kat".to_string()]),
+                ("kat-NOM-PL", vec!["kadi".to_string()]),
+                ("kat-ACC-SG", vec!["kadem".to_string()]),
+                ("kat-ACC-PL", vec!["kademi".to_string()]),
+            ]
+        );
+        assert_eq!(
+            sound_system.inflect_paradigm("verb", "kat"),
+            Err("unknown paradigm")
+        );
+    }
+}
@@ -0,0 +1,110 @@
+use super::compound::MORPHEME_BOUNDARY;
+use super::generator::number_of_syllables;
+use super::{Distribution, MonoSyllableRepartition, NameProfile, SoundSystem};
+use rand::prelude::*;
+use std::collections::HashMap;
+
+/// Attempts to find a new name before giving up.
+const ATTEMPTS: usize = 20;
+
+impl SoundSystem {
+    pub fn name_profile(&self, name: &'_ str) -> Option<&NameProfile> {
+        self.names()
+            .iter()
+            .find(|profile| profile.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Generates names of the kind of `profile`, a prefix and a suffix
+    /// being separated from the stem by the morpheme boundary `+` so that
+    /// the rules can refer to it. Without lengths the names are as long as
+    /// the words, one syllable being as frequent as `repartition`.
+    pub fn generate_names(
+        &self,
+        profile: &NameProfile,
+        number: usize,
+        repartition: MonoSyllableRepartition,
+    ) -> Vec<String> {
+        let patterns = if profile.syllables.is_empty() {
+            self.syllables()
+        } else {
+            &profile.syllables
+        };
+        let mut distribution_cache: HashMap<String, Vec<Distribution>> = HashMap::new();
+        let mut rng = thread_rng();
+        let boundary = MORPHEME_BOUNDARY.to_string();
+        let mut names = vec![];
+        for _ in 0..number * ATTEMPTS {
+            if names.len() == number {
+                break;
+            }
+            let length = profile
+                .lengths
+                .choose_weighted(&mut rng, |(_, weight)| *weight)
+                .map(|(length, _)| *length)
+                .unwrap_or_else(|_| number_of_syllables(repartition));
+            let mut parts = vec![];
+            parts.extend(profile.prefixes.choose(&mut rng).cloned());
+            parts.push(self.word(patterns, length, &mut distribution_cache));
+            parts.extend(profile.suffixes.choose(&mut rng).cloned());
+            let name = parts.join(&boundary);
+            if !names.contains(&name) {
+                names.push(name);
+            }
+        }
+        names
+    }
+}
+
+impl NameProfile {
+    /// Name as written, with a capital letter when the profile has one.
+    pub fn format(&self, name: &'_ str) -> String {
+        let mut chars = name.chars();
+        match chars.next() {
+            Some(first) if self.capitalize => first.to_uppercase().chain(chars).collect(),
+            _ => name.to_string(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sound_system() -> SoundSystem {
+        crate::sound_system::from_string(
+            "letters: a, i, u, k, t, p, m, n\nsyllables: CV CVC\n\
+             names river:\npatterns: CV\nlength: 1: 3, 2\nsuffix: -ara\ncapitalize\n\
+             rules:\n_+V: a -> \n",
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_name_profile() {
+        let sound_system = sound_system();
+        let profile = sound_system.name_profile("River").unwrap();
+        assert_eq!(profile.syllables, vec![vec!["C", "V"]]);
+        assert_eq!(profile.lengths, vec![(1, 3.0), (2, 1.0)]);
+        assert_eq!(profile.suffixes, vec!["ara"]);
+        assert!(profile.prefixes.is_empty());
+        assert_eq!(profile.format("kuara"), "Kuara");
+        assert!(sound_system.name_profile("mountain").is_none());
+    }
+
+    #[test]
+    fn test_generate_names() {
+        let mut sound_system = sound_system();
+        let profile = sound_system.name_profile("river").unwrap().clone();
+        let names = sound_system.generate_names(&profile, 5, MonoSyllableRepartition::Never);
+        assert!(!names.is_empty());
+        names.iter().for_each(|name| {
+            let parts = name.split(MORPHEME_BOUNDARY).collect::<Vec<_>>();
+            assert_eq!(parts[1], "ara");
+            assert!(parts[0].len() == 2 || parts[0].len() == 4);
+        });
+        let output = sound_system
+            .sound_trasformation(vec!["ka+ara".to_string()])
+            .output;
+        assert_eq!(profile.format(&output[0][0]), "Kara");
+    }
+}
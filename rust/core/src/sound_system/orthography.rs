@@ -0,0 +1,192 @@
+use super::phone::Phone;
+use super::{Condition, ConditionOperand, ConditionType, Grapheme, Orthography, SoundSystem};
+
+impl SoundSystem {
+    pub fn orthography(&self, name: &'_ str) -> Option<&Orthography> {
+        self.orthographies()
+            .iter()
+            .find(|orthography| orthography.name.eq_ignore_ascii_case(name))
+    }
+
+    /// Spells `word` in the orthography `name` from its phones.
+    /// Phones without grapheme keep their symbol.
+    pub fn spell(&self, word: &'_ str, name: &'_ str) -> Option<String> {
+        self.orthography(name).map(|orthography| {
+            let phones = self.phones(word);
+            let mut graphemes = orthography.graphemes.iter().collect::<Vec<_>>();
+            graphemes.sort_by(|left, right| {
+                right
+                    .phones
+                    .len()
+                    .cmp(&left.phones.len())
+                    .then(Ord::cmp(&right.condition, &left.condition))
+            });
+            let mut result = String::new();
+            let mut position = 0;
+            while position < phones.len() {
+                match graphemes
+                    .iter()
+                    .find(|grapheme| self.grapheme_matches(&phones, position, grapheme))
+                {
+                    Some(grapheme) => {
+                        result.push_str(&grapheme.letters);
+                        position += grapheme.phones.len();
+                    }
+                    None => {
+                        result.push_str(
+                            &self
+                                .inventory()
+                                .representation(&phones[position..=position]),
+                        );
+                        position += 1;
+                    }
+                }
+            }
+            result
+        })
+    }
+
+    /// All the spellings of `word`, in the order of the orthographies.
+    pub fn spellings(&self, word: &'_ str) -> Vec<(String, String)> {
+        self.orthographies()
+            .iter()
+            .filter_map(|orthography| {
+                self.spell(word, &orthography.name)
+                    .map(|spelling| (orthography.name.clone(), spelling))
+            })
+            .collect()
+    }
+
+    fn grapheme_matches(&self, phones: &[Phone], position: usize, grapheme: &Grapheme) -> bool {
+        !grapheme.phones.is_empty()
+            && phones[position..].starts_with(&grapheme.phones)
+            && self.resolve_phone_condition(
+                phones,
+                position,
+                grapheme.phones.len(),
+                &grapheme.condition,
+            )
+    }
+
+    fn resolve_phone_condition(
+        &self,
+        phones: &[Phone],
+        position: usize,
+        length: usize,
+        condition: &Condition,
+    ) -> bool {
+        match condition {
+            Condition::Always => true,
+            Condition::Single(cond_type) => {
+                self.resolve_phone_condition_type(phones, position, length, cond_type)
+            }
+            Condition::Not(cond_type) => {
+                !self.resolve_phone_condition_type(phones, position, length, cond_type)
+            }
+            Condition::Binary {
+                operand,
+                left,
+                right,
+            } => {
+                let left_bool = self.resolve_phone_condition(phones, position, length, left);
+                let right_bool = self.resolve_phone_condition(phones, position, length, right);
+                match operand {
+                    ConditionOperand::And => left_bool && right_bool,
+                    ConditionOperand::Or => left_bool || right_bool,
+                }
+            }
+        }
+    }
+
+    fn resolve_phone_condition_type(
+        &self,
+        phones: &[Phone],
+        position: usize,
+        length: usize,
+        condition: &ConditionType,
+    ) -> bool {
+        match condition {
+            ConditionType::BeginningWord => position == 0,
+            ConditionType::EndWord => position + length == phones.len(),
+            ConditionType::FollowedBy(value) => self.phones_match(phones, position + length, value),
+            ConditionType::Between(before, after) => {
+                let size = before.chars().count();
+                size <= position
+                    && self.phones_match(phones, position - size, before)
+                    && self.phones_match(phones, position + length, after)
+            }
+            ConditionType::None => false,
+        }
+    }
+
+    /// Each character of `value` is either a class or a symbol.
+    fn phones_match(&self, phones: &[Phone], start: usize, value: &'_ str) -> bool {
+        value.chars().enumerate().all(|(i, c)| {
+            phones
+                .get(start + i)
+                .map(|phone| {
+                    if c.is_uppercase() {
+                        self.phone_in_class(phone, &c.to_string())
+                    } else {
+                        self.inventory().phone(c) == Some(*phone)
+                    }
+                })
+                .unwrap_or(false)
+        })
+    }
+
+    pub(super) fn phone_in_class(&self, phone: &Phone, class_name: &'_ str) -> bool {
+        self.classes()
+            .get(class_name)
+            .map(|letters| {
+                letters
+                    .iter()
+                    .any(|letter| self.phones(letter).as_slice() == [*phone])
+            })
+            .unwrap_or(false)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    const INPUT: &str = "letters: s, h, a, i, k, c
+phonemes:
+  c /k/
+  sh /ʃ/
+orthography latin:
+  /ʃ/ sh
+  /k/ c
+  /k/ k followed by \"H\"
+orthography \"Learner\":
+  /ʃ/ sch
+  /a/ ah at the end of word
+";
+
+    #[test]
+    fn test_spell() {
+        let sound_system = crate::sound_system::from_string(INPUT).unwrap();
+        assert_eq!(sound_system.ipa_representation("shaki"), "ʃa.ki");
+        assert_eq!(
+            sound_system.spell("shaki", "latin"),
+            Some("shaki".to_string())
+        );
+        assert_eq!(sound_system.spell("kak", "latin"), Some("cac".to_string()));
+        assert_eq!(
+            sound_system.spell("sha", "learner"),
+            Some("schah".to_string())
+        );
+        assert_eq!(sound_system.spell("sha", "cyrillic"), None);
+    }
+
+    #[test]
+    fn test_spellings() {
+        let sound_system = crate::sound_system::from_string(INPUT).unwrap();
+        assert_eq!(
+            sound_system.spellings("ka"),
+            vec![
+                ("latin".to_string(), "ca".to_string()),
+                ("Learner".to_string(), "kah".to_string())
+            ]
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use super::*;
+
+/// Distance between a consonant and a vowel, or a diacritic and a sound.
+const KIND_DISTANCE: u32 = 20;
+/// Distance between two different diacritics.
+const DIACRITIC_DISTANCE: u32 = 10;
+/// Phones this far apart count as a whole edit when aligning words.
+const EDIT_DISTANCE: u32 = 10;
+
+impl Phone {
+    /// How far two phones are by their features: place, manner and phonation
+    /// for consonants, height, backness and roundness for vowels.
+    /// The same phone is at 0.
+    pub fn distance(&self, other: &Phone) -> u32 {
+        match (self, other) {
+            (Phone::Consonant(left), Phone::Consonant(right)) => {
+                (left.place as u32).abs_diff(right.place as u32)
+                    + manner_distance(left.manner, right.manner)
+                    + if left.phonation == right.phonation {
+                        0
+                    } else {
+                        2
+                    }
+            }
+            (Phone::Vowel(left), Phone::Vowel(right)) => {
+                (left.height as u32).abs_diff(right.height as u32)
+                    + 2 * (left.backness as u32).abs_diff(right.backness as u32)
+                    + match (left.roundness, right.roundness) {
+                        (left, right) if left == right => 0,
+                        (Roundness::Undefined, _) | (_, Roundness::Undefined) => 1,
+                        _ => 2,
+                    }
+            }
+            (Phone::Diacritic(left), Phone::Diacritic(right)) if left == right => 0,
+            (Phone::Diacritic(_), Phone::Diacritic(_)) => DIACRITIC_DISTANCE,
+            _ => KIND_DISTANCE,
+        }
+    }
+}
+
+/// Weighted edit distance between two sequences of phones.
+/// Adding or removing a sound costs 1 and a diacritic 0.5,
+/// replacing a phone costs its distance to the other one, at most 1.
+pub fn alignment_distance(left: &[Phone], right: &[Phone]) -> f64 {
+    let edit = |phone: &Phone| match phone {
+        Phone::Diacritic(_) => 0.5,
+        _ => 1.0,
+    };
+    let mut previous = vec![0.0; right.len() + 1];
+    for (index, phone) in right.iter().enumerate() {
+        previous[index + 1] = previous[index] + edit(phone);
+    }
+    for phone in left {
+        let mut current = vec![previous[0] + edit(phone); right.len() + 1];
+        for (index, other) in right.iter().enumerate() {
+            let substitution =
+                f64::from(phone.distance(other).min(EDIT_DISTANCE)) / f64::from(EDIT_DISTANCE);
+            current[index + 1] = (previous[index] + substitution)
+                .min(previous[index + 1] + edit(phone))
+                .min(current[index] + edit(other));
+        }
+        previous = current;
+    }
+    previous[right.len()]
+}
+
+/// Manners are ranked by sonority, two manners of the same rank
+/// such as a trill and a tap being close.
+fn manner_distance(left: ConsonantManner, right: ConsonantManner) -> u32 {
+    if left == right {
+        return 0;
+    }
+    let rank = |manner| -> u32 {
+        match manner {
+            ConsonantManner::Stop => 0,
+            ConsonantManner::SibilantFricative
+            | ConsonantManner::Fricative
+            | ConsonantManner::LateralFricative => 1,
+            ConsonantManner::Nasal => 2,
+            ConsonantManner::Trill | ConsonantManner::Tap | ConsonantManner::LateralTap => 3,
+            ConsonantManner::Approximant | ConsonantManner::LateralApproximant => 4,
+        }
+    };
+    1 + 2 * rank(left).abs_diff(rank(right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::convert::TryFrom;
+
+    fn distance(left: char, right: char) -> u32 {
+        Phone::try_from(left)
+            .unwrap()
+            .distance(&Phone::try_from(right).unwrap())
+    }
+
+    #[test]
+    fn test_distance() {
+        assert_eq!(distance('t', 't'), 0);
+        assert_eq!(distance('t', 'd'), 2);
+        assert!(distance('θ', 't') < distance('θ', 'k'));
+        assert!(distance('θ', 's') < distance('θ', 'm'));
+        assert!(distance('ɪ', 'i') < distance('ɪ', 'a'));
+        assert!(distance('y', 'i') < distance('y', 'u'));
+        assert_eq!(distance('a', 't'), KIND_DISTANCE);
+    }
+
+    #[test]
+    fn test_alignment_distance() {
+        let phones = |word: &str| {
+            word.chars()
+                .map(|symbol| Phone::try_from(symbol).unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(alignment_distance(&phones("kata"), &phones("kata")), 0.0);
+        assert_eq!(alignment_distance(&phones("kata"), &phones("kat")), 1.0);
+        assert_eq!(alignment_distance(&phones("kata"), &phones("")), 4.0);
+        assert_eq!(alignment_distance(&phones("kata"), &phones("kada")), 0.2);
+        assert_eq!(alignment_distance(&phones("kata"), &phones("katʰa")), 0.5);
+        assert!(
+            alignment_distance(&phones("kata"), &phones("kasa"))
+                < alignment_distance(&phones("kata"), &phones("kama"))
+        );
+        assert!(alignment_distance(&phones("kata"), &phones("ipu")) > 2.0);
+    }
+}
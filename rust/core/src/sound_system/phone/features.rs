@@ -0,0 +1,238 @@
+use super::*;
+use std::str::FromStr;
+
+impl Phone {
+    pub fn from_features(features: &[&str]) -> Result<Self, &'static str> {
+        match features {
+            ["consonant", place, manner, phonation] => Ok(Phone::Consonant(Consonant {
+                place: place.parse()?,
+                manner: manner.parse()?,
+                phonation: phonation.parse()?,
+            })),
+            ["vowel", height, backness, roundness] => Ok(Phone::Vowel(Vowel {
+                height: height.parse()?,
+                backness: backness.parse()?,
+                roundness: roundness.parse()?,
+            })),
+            [kind, value] => {
+                let property = match *kind {
+                    "release" => PhoneProperty::ConsonantRelease(value.parse()?),
+                    "phonation" => PhoneProperty::Phonation(value.parse()?),
+                    "articulation" => PhoneProperty::Articulation(value.parse()?),
+                    "coarticulation" => PhoneProperty::CoArticulation(value.parse()?),
+                    "rhythm" => PhoneProperty::Rhythm(value.parse()?),
+                    "tone" => PhoneProperty::Tone(value.parse()?),
+                    "intonation" => PhoneProperty::Intonation(value.parse()?),
+                    _ => return Err("unknown diacritic kind"),
+                };
+                Ok(Phone::Diacritic(property))
+            }
+            _ => Err("invalid phone definition"),
+        }
+    }
+}
+
+impl FromStr for ConsonantPlace {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "bilabial" => Ok(ConsonantPlace::Bilabial),
+            "labio_dental" => Ok(ConsonantPlace::LabioDental),
+            "dental" => Ok(ConsonantPlace::Dental),
+            "alveolar" => Ok(ConsonantPlace::Alveolar),
+            "post_alveolar" => Ok(ConsonantPlace::PostAlveolar),
+            "retroflex" => Ok(ConsonantPlace::Retroflex),
+            "palatal" => Ok(ConsonantPlace::Palatal),
+            "velar" => Ok(ConsonantPlace::Velar),
+            "labio_velar" => Ok(ConsonantPlace::LabioVelar),
+            "uvular" => Ok(ConsonantPlace::Uvular),
+            "pharyngeal" => Ok(ConsonantPlace::Pharyngeal),
+            "glottal" => Ok(ConsonantPlace::Glottal),
+            _ => Err("unknown consonant place"),
+        }
+    }
+}
+
+impl FromStr for ConsonantManner {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "nasal" => Ok(ConsonantManner::Nasal),
+            "stop" => Ok(ConsonantManner::Stop),
+            "sibilant_fricative" => Ok(ConsonantManner::SibilantFricative),
+            "fricative" => Ok(ConsonantManner::Fricative),
+            "trill" => Ok(ConsonantManner::Trill),
+            "tap" => Ok(ConsonantManner::Tap),
+            "lateral_fricative" => Ok(ConsonantManner::LateralFricative),
+            "approximant" => Ok(ConsonantManner::Approximant),
+            "lateral_approximant" => Ok(ConsonantManner::LateralApproximant),
+            "lateral_tap" => Ok(ConsonantManner::LateralTap),
+            _ => Err("unknown consonant manner"),
+        }
+    }
+}
+
+impl FromStr for Phonation {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "voiceless" => Ok(Phonation::Voiceless),
+            "voiced" => Ok(Phonation::Voiced),
+            "breathy_voiced" => Ok(Phonation::BreathyVoiced),
+            "creaky_voiced" => Ok(Phonation::CreakyVoiced),
+            _ => Err("unknown phonation"),
+        }
+    }
+}
+
+impl FromStr for Height {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "close" => Ok(Height::Close),
+            "near_close" => Ok(Height::NearClose),
+            "close_mid" => Ok(Height::CloseMid),
+            "mid" => Ok(Height::Mid),
+            "open_mid" => Ok(Height::OpenMid),
+            "near_open" => Ok(Height::NearOpen),
+            "open" => Ok(Height::Open),
+            _ => Err("unknown vowel height"),
+        }
+    }
+}
+
+impl FromStr for Backness {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "front" => Ok(Backness::Front),
+            "central" => Ok(Backness::Central),
+            "back" => Ok(Backness::Back),
+            _ => Err("unknown vowel backness"),
+        }
+    }
+}
+
+impl FromStr for Roundness {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "unrounded" => Ok(Roundness::UnRounded),
+            "rounded" => Ok(Roundness::Rounded),
+            "undefined" => Ok(Roundness::Undefined),
+            _ => Err("unknown vowel roundness"),
+        }
+    }
+}
+
+impl FromStr for ConsonantRelease {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "aspirated" => Ok(ConsonantRelease::Aspirated),
+            "no_audible" => Ok(ConsonantRelease::NoAudible),
+            "nasal" => Ok(ConsonantRelease::Nasal),
+            "lateral" => Ok(ConsonantRelease::Lateral),
+            "dental" => Ok(ConsonantRelease::Dental),
+            "velar" => Ok(ConsonantRelease::Velar),
+            "mid_central_vowel" => Ok(ConsonantRelease::MidCentralVowel),
+            _ => Err("unknown consonant release"),
+        }
+    }
+}
+
+impl FromStr for Articulation {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "dental" => Ok(Articulation::Dental),
+            "linguolabial" => Ok(Articulation::Linguolabial),
+            "apical" => Ok(Articulation::Apical),
+            "laminal" => Ok(Articulation::Laminal),
+            "advanced" => Ok(Articulation::Advanced),
+            "retracted" => Ok(Articulation::Retracted),
+            "raised" => Ok(Articulation::Raised),
+            "lowered" => Ok(Articulation::Lowered),
+            "centralized" => Ok(Articulation::Centralized),
+            "mid_centralized" => Ok(Articulation::MidCentralized),
+            _ => Err("unknown articulation"),
+        }
+    }
+}
+
+impl FromStr for CoArticulation {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "more_rounded" => Ok(CoArticulation::MoreRounded),
+            "less_rounded" => Ok(CoArticulation::LessRounded),
+            "labialized" => Ok(CoArticulation::Labialized),
+            "palatalized" => Ok(CoArticulation::Palatalized),
+            "velarized" => Ok(CoArticulation::Velarized),
+            "pharyngealized" => Ok(CoArticulation::Pharyngealized),
+            "velarized_or_pharyngealized" => Ok(CoArticulation::VelarizedOrPharyngealized),
+            "advanced_tongue_root" => Ok(CoArticulation::AdvancedTongueRoot),
+            "retracted_tongue_root" => Ok(CoArticulation::RetractedTongueRoot),
+            "nasalized" => Ok(CoArticulation::Nasalized),
+            "rhoticity" => Ok(CoArticulation::Rhoticity),
+            _ => Err("unknown co-articulation"),
+        }
+    }
+}
+
+impl FromStr for Rhythm {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "primary_stress" => Ok(Rhythm::PrimaryStress),
+            "secondary_stress" => Ok(Rhythm::SecondaryStress),
+            "long" => Ok(Rhythm::Long),
+            "half_long" => Ok(Rhythm::HalfLong),
+            "extra_short" => Ok(Rhythm::ExtraShort),
+            _ => Err("unknown rhythm"),
+        }
+    }
+}
+
+impl FromStr for Tone {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "top" => Ok(Tone::Top),
+            "upstep" => Ok(Tone::Upstep),
+            "high" => Ok(Tone::High),
+            "rising" => Ok(Tone::Rising),
+            "mid" => Ok(Tone::Mid),
+            "low" => Ok(Tone::Low),
+            "falling" => Ok(Tone::Falling),
+            "bottom" => Ok(Tone::Bottom),
+            "downstep" => Ok(Tone::Downstep),
+            _ => Err("unknown tone"),
+        }
+    }
+}
+
+impl FromStr for Intonation {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "minor_break" => Ok(Intonation::MinorBreak),
+            "major_break" => Ok(Intonation::MajorBreak),
+            "global_rise" => Ok(Intonation::GlobalRise),
+            "global_fall" => Ok(Intonation::GlobalFall),
+            _ => Err("unknown intonation"),
+        }
+    }
+}
@@ -0,0 +1,212 @@
+use super::Phone;
+use crate::wgl::{self, PhoneDefinition};
+use std::char;
+use std::collections::HashMap;
+
+lazy_static! {
+    static ref IPA: Inventory = {
+        let definitions =
+            wgl::inventory_from_string(include_str!("ipa.phones")).expect("Invalid IPA inventory");
+        let mut inventory = Inventory {
+            phones: HashMap::new(),
+            symbols: HashMap::new(),
+            order: vec![],
+        };
+        definitions.iter().for_each(|definition| {
+            let (symbol, phone) = parse_definition(definition).expect("Invalid IPA phone");
+            if inventory.phones.insert(symbol, phone).is_none() {
+                inventory.order.push(symbol);
+            }
+            inventory.symbols.entry(phone).or_insert(symbol);
+        });
+        inventory
+    };
+}
+
+/// Bidirectional mapping between symbols and phones.
+///
+/// The default inventory is the IPA one, it can be extended
+/// or overridden with `phones:` in WGL.
+#[derive(Debug, Clone, Serialize)]
+pub struct Inventory {
+    phones: HashMap<char, Phone>,
+    #[serde(skip)]
+    symbols: HashMap<Phone, char>,
+    /// Symbols in the order of their definition.
+    #[serde(skip)]
+    order: Vec<char>,
+}
+
+impl Default for Inventory {
+    fn default() -> Self {
+        IPA.clone()
+    }
+}
+
+impl Inventory {
+    pub fn ipa() -> &'static Self {
+        &IPA
+    }
+
+    pub fn phone(&self, symbol: char) -> Option<Phone> {
+        self.phones.get(&symbol).copied()
+    }
+
+    pub fn symbol(&self, phone: &Phone) -> Option<char> {
+        self.symbols.get(phone).copied()
+    }
+
+    pub fn contains(&self, phone: &Phone) -> bool {
+        self.symbols.contains_key(phone)
+    }
+
+    /// Defines `symbol` as `phone`.
+    /// The symbol becomes the one used to render the phone. The phone it
+    /// stood for is then rendered with its first other symbol, if any.
+    pub fn define(&mut self, symbol: char, phone: Phone) {
+        match self.phones.insert(symbol, phone) {
+            Some(previous) if self.symbols.get(&previous) == Some(&symbol) => {
+                self.symbols.remove(&previous);
+                let phones = &self.phones;
+                if let Some(other) = self
+                    .order
+                    .iter()
+                    .find(|other| phones.get(other) == Some(&previous))
+                {
+                    self.symbols.insert(previous, *other);
+                }
+            }
+            Some(_) => {}
+            None => self.order.push(symbol),
+        }
+        self.symbols.insert(phone, symbol);
+    }
+
+    pub fn extend(&mut self, definitions: &[PhoneDefinition<'_>]) {
+        definitions
+            .iter()
+            .for_each(|definition| match parse_definition(definition) {
+                Ok((symbol, phone)) => self.define(symbol, phone),
+                Err(e) => warn!("Ignoring phone {}: {}", definition.0, e),
+            });
+    }
+
+    pub fn phones(&self, notation: &'_ str) -> Vec<Phone> {
+        notation.chars().filter_map(|c| self.phone(c)).collect()
+    }
+
+    /// Symbols of `notation` that are not in the inventory.
+    pub fn unknown_symbols(&self, notation: &'_ str) -> Vec<String> {
+        notation
+            .chars()
+            .filter(|c| self.phone(*c).is_none())
+            .map(String::from)
+            .collect()
+    }
+
+    pub fn representation(&self, phones: &[Phone]) -> String {
+        phones
+            .iter()
+            .filter_map(|phone| self.symbol(phone))
+            .collect()
+    }
+}
+
+fn parse_definition(
+    (symbol, features): &PhoneDefinition<'_>,
+) -> Result<(char, Phone), &'static str> {
+    let mut chars = decode_symbol(symbol)?.chars().collect::<Vec<_>>();
+    if chars.len() != 1 {
+        return Err("a phone symbol must be a single character");
+    }
+    let symbol = chars.remove(0);
+    Phone::from_features(features).map(|phone| (symbol, phone))
+}
+
+/// Replaces the `\u{XXXX}` escapes of a symbol by their character.
+pub(super) fn decode_symbol(symbol: &'_ str) -> Result<String, &'static str> {
+    let mut result = String::new();
+    let mut rest = symbol;
+    while let Some(start) = rest.find("\\u{") {
+        result.push_str(&rest[..start]);
+        let escape = &rest[start + 3..];
+        let end = escape.find('}').ok_or("invalid unicode escape")?;
+        let c = u32::from_str_radix(&escape[..end], 16)
+            .ok()
+            .and_then(char::from_u32)
+            .ok_or("invalid unicode escape")?;
+        result.push(c);
+        rest = &escape[end + 1..];
+    }
+    result.push_str(rest);
+    if result.is_empty() {
+        Err("empty symbol")
+    } else {
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::*;
+    use super::*;
+    use std::convert::TryFrom;
+
+    #[test]
+    fn test_round_trip() {
+        let inventory = Inventory::ipa();
+        let phone = inventory.phone('ʃ').unwrap();
+        assert_eq!(
+            phone,
+            Phone::Consonant(Consonant {
+                place: ConsonantPlace::PostAlveolar,
+                manner: ConsonantManner::SibilantFricative,
+                phonation: Phonation::Voiceless,
+            })
+        );
+        assert_eq!(inventory.symbol(&phone), Some('ʃ'));
+        assert_eq!(
+            inventory.phone('\u{303}').map(|p| p.to_string()),
+            Some("\u{303}".to_string())
+        );
+    }
+
+    #[test]
+    fn test_first_symbol_is_canonical() {
+        let inventory = Inventory::ipa();
+        let phone = inventory.phone('ɚ').unwrap();
+        assert_eq!(inventory.symbol(&phone), Some('ə'));
+    }
+
+    #[test]
+    fn test_define_overrides_both_directions() {
+        let mut inventory = Inventory::default();
+        inventory.extend(&[("ɿ", vec!["vowel", "close", "central", "unrounded"])]);
+        let phone = inventory.phone('ɨ').unwrap();
+        assert_eq!(inventory.phone('ɿ'), Some(phone));
+        assert_eq!(inventory.symbol(&phone), Some('ɿ'));
+        assert_eq!(Inventory::ipa().symbol(&phone), Some('ɨ'));
+    }
+
+    #[test]
+    fn test_redefined_symbol_falls_back_in_file_order() {
+        let mut inventory = Inventory::default();
+        let schwa = inventory.phone('ə').unwrap();
+        let mid_central = vec!["vowel", "mid", "central", "undefined"];
+        inventory.extend(&[("Ə", mid_central.clone()), ("Ǝ", mid_central)]);
+        assert_eq!(inventory.symbol(&schwa), Some('Ǝ'));
+        inventory.extend(&[("Ǝ", vec!["vowel", "close", "central", "unrounded"])]);
+        assert_eq!(inventory.symbol(&schwa), Some('ə'));
+        assert_eq!(inventory.unknown_symbols("aX9"), vec!["X", "9"]);
+    }
+
+    #[test]
+    fn test_unknown_phone() {
+        let phone = Phone::Consonant(Consonant {
+            place: ConsonantPlace::Pharyngeal,
+            manner: ConsonantManner::LateralTap,
+            phonation: Phonation::CreakyVoiced,
+        });
+        assert_eq!(char::try_from(phone), Err("no match"));
+    }
+}
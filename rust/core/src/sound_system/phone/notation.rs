@@ -0,0 +1,214 @@
+use super::inventory::decode_symbol;
+use super::{Inventory, Phone};
+use crate::wgl;
+use std::collections::HashMap;
+use std::fmt;
+use std::str::FromStr;
+
+lazy_static! {
+    static ref XSAMPA: Notation = Notation::parse(include_str!("xsampa.phones"));
+    static ref KIRSHENBAUM: Notation = Notation::parse(include_str!("kirshenbaum.phones"));
+}
+
+/// Way of writing phones.
+#[derive(Debug, Default, Eq, PartialEq, Serialize, Clone, Copy)]
+pub enum Transcription {
+    #[default]
+    Ipa,
+    XSampa,
+    Kirshenbaum,
+}
+
+impl FromStr for Transcription {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "ipa" => Ok(Transcription::Ipa),
+            "xsampa" | "x-sampa" => Ok(Transcription::XSampa),
+            "kirshenbaum" => Ok(Transcription::Kirshenbaum),
+            _ => Err("no match"),
+        }
+    }
+}
+
+impl fmt::Display for Transcription {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let s = match self {
+            Transcription::Ipa => "IPA",
+            Transcription::XSampa => "X-SAMPA",
+            Transcription::Kirshenbaum => "Kirshenbaum",
+        };
+        write!(f, "{}", s)
+    }
+}
+
+impl Transcription {
+    /// Reads phones written in this transcription.
+    /// Unknown symbols are skipped with a warning.
+    pub fn phones(self, inventory: &Inventory, text: &'_ str) -> Vec<Phone> {
+        let unknown = self.unknown_symbols(inventory, text);
+        if !unknown.is_empty() {
+            warn!(
+                "Ignoring unknown {} symbols in /{}/: {}",
+                self,
+                text,
+                unknown.join(" ")
+            );
+        }
+        match self.notation() {
+            None => inventory.phones(text),
+            Some(notation) => notation.phones(text),
+        }
+    }
+
+    /// Symbols of `text` that this transcription does not know.
+    pub fn unknown_symbols(self, inventory: &Inventory, text: &'_ str) -> Vec<String> {
+        match self.notation() {
+            None => inventory.unknown_symbols(text),
+            Some(notation) => notation.tokens(text).1,
+        }
+    }
+
+    /// Writes phones in this transcription.
+    /// Phones without an ASCII symbol fall back to their IPA one.
+    pub fn representation(self, inventory: &Inventory, phones: &[Phone]) -> String {
+        match self.notation() {
+            None => inventory.representation(phones),
+            Some(notation) => phones
+                .iter()
+                .filter_map(|phone| {
+                    notation
+                        .symbol(phone)
+                        .map(str::to_string)
+                        .or_else(|| inventory.symbol(phone).map(String::from))
+                })
+                .collect(),
+        }
+    }
+
+    /// Kirshenbaum has no syllable boundary, `.` marks retroflexion.
+    pub fn syllable_separator(self) -> &'static str {
+        match self {
+            Transcription::Kirshenbaum => "",
+            _ => ".",
+        }
+    }
+
+    fn notation(self) -> Option<&'static Notation> {
+        match self {
+            Transcription::Ipa => None,
+            Transcription::XSampa => Some(&XSAMPA),
+            Transcription::Kirshenbaum => Some(&KIRSHENBAUM),
+        }
+    }
+}
+
+/// ASCII notation where a phone can be written with several characters.
+#[derive(Debug)]
+struct Notation {
+    phones: HashMap<String, Phone>,
+    symbols: HashMap<Phone, String>,
+    longest: usize,
+}
+
+impl Notation {
+    fn parse(input: &'static str) -> Self {
+        let mut notation = Notation {
+            phones: HashMap::new(),
+            symbols: HashMap::new(),
+            longest: 0,
+        };
+        wgl::inventory_from_string(input)
+            .expect("Invalid notation")
+            .iter()
+            .for_each(|(symbol, features)| {
+                let symbol = decode_symbol(symbol).expect("Invalid notation symbol");
+                let phone = Phone::from_features(features).expect("Invalid notation phone");
+                notation.longest = notation.longest.max(symbol.chars().count());
+                notation
+                    .symbols
+                    .entry(phone)
+                    .or_insert_with(|| symbol.clone());
+                notation.phones.insert(symbol, phone);
+            });
+        notation
+    }
+
+    fn symbol(&self, phone: &Phone) -> Option<&str> {
+        self.symbols.get(phone).map(String::as_str)
+    }
+
+    fn phones(&self, text: &'_ str) -> Vec<Phone> {
+        self.tokens(text).0
+    }
+
+    /// Longest match tokenisation, so `r\`` is read before `r\` and `r`.
+    /// Returns the phones and the unknown symbols.
+    fn tokens(&self, text: &'_ str) -> (Vec<Phone>, Vec<String>) {
+        let chars = text.chars().collect::<Vec<_>>();
+        let mut result = vec![];
+        let mut unknown = vec![];
+        let mut position = 0;
+        while position < chars.len() {
+            let longest = self.longest.min(chars.len() - position);
+            let found = (1..=longest).rev().find_map(|size| {
+                let token = chars[position..position + size].iter().collect::<String>();
+                self.phones.get(&token).map(|phone| (size, *phone))
+            });
+            match found {
+                Some((size, phone)) => {
+                    result.push(phone);
+                    position += size;
+                }
+                None => {
+                    unknown.push(chars[position].to_string());
+                    position += 1;
+                }
+            }
+        }
+        (result, unknown)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xsampa_to_ipa() {
+        let inventory = Inventory::ipa();
+        let phones = Transcription::XSampa.phones(inventory, "\"tS{r\\`@_~:");
+        assert_eq!(inventory.representation(&phones), "ˈtʃæɻə\u{303}ː");
+    }
+
+    #[test]
+    fn test_ipa_to_ascii() {
+        let inventory = Inventory::ipa();
+        let phones = inventory.phones("ˌʃiŋʁ");
+        assert_eq!(
+            Transcription::XSampa.representation(inventory, &phones),
+            "%SiNR"
+        );
+        assert_eq!(
+            Transcription::Kirshenbaum.representation(inventory, &phones),
+            ",SiNg\""
+        );
+    }
+
+    #[test]
+    fn test_unknown_symbols() {
+        let inventory = Inventory::ipa();
+        assert_eq!(
+            Transcription::XSampa.unknown_symbols(inventory, "tS§a"),
+            vec!["§"]
+        );
+        assert_eq!(
+            Transcription::Ipa.unknown_symbols(inventory, "taX"),
+            vec!["X"]
+        );
+        assert!(Transcription::Ipa
+            .unknown_symbols(inventory, "ʃa")
+            .is_empty());
+    }
+}
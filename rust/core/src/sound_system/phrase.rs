@@ -0,0 +1,287 @@
+use super::compound::MORPHEME_BOUNDARY;
+use super::phone::Transcription;
+use super::rules::{is_marker, split_markers, History, Transformation};
+use super::{Pattern, SoundSystem};
+
+/// Boundary between the words of a phrase going through the rules,
+/// written `#` in the environments.
+pub const WORD_BOUNDARY: char = '#';
+
+/// Punctuation outside of ASCII kept between the words of a phrase.
+const PUNCTUATION: &str = "«»“”„‘’‹›—–…¿¡·";
+
+impl SoundSystem {
+    /// Applies the rules from the stage `from` to the stage `until` to phrases.
+    ///
+    /// The words of a phrase go through the rules one by one, `#_` and `_#`
+    /// matching their edges. A rule with `#` inside its environment, such as
+    /// `_#V: s -> z`, crosses the words. The whitespace and the punctuation
+    /// are kept. The `!name` markers escape the rules for the whole phrase.
+    pub fn phrase_transformation(
+        &mut self,
+        phrases: Vec<String>,
+        from: Option<&'_ str>,
+        until: Option<&'_ str>,
+    ) -> Result<Transformation, &'static str> {
+        let boundary = WORD_BOUNDARY.to_string();
+        let (split, markers): (Vec<_>, Vec<_>) = phrases
+            .iter()
+            .map(|phrase| {
+                let (phrase, markers) = split_markers(phrase);
+                (self.split_phrase(&phrase), markers)
+            })
+            .unzip();
+        let words = split
+            .iter()
+            .zip(markers.iter())
+            .map(|((_, words), markers)| {
+                let mut input = words.join(&boundary);
+                markers
+                    .iter()
+                    .for_each(|marker| input.push_str(&format!(" !{}", marker)));
+                input
+            })
+            .collect();
+        let transformation = self.stage_transformation(words, from, until)?;
+        let join = |words: &Vec<Vec<String>>| -> Vec<Vec<String>> {
+            words
+                .iter()
+                .zip(split.iter())
+                .map(|(variants, (separators, _))| {
+                    variants
+                        .iter()
+                        .map(|variant| join_phrase(separators, variant))
+                        .collect()
+                })
+                .collect()
+        };
+        Ok(Transformation {
+            output: join(&transformation.output),
+            history: transformation
+                .history
+                .iter()
+                .map(|history| History {
+                    rule: history.rule.clone(),
+                    words: join(&history.words),
+                    stage: history.stage.clone(),
+                    blocked: history.blocked.clone(),
+                })
+                .collect(),
+        })
+    }
+
+    /// Applies `rule` to each word of a phrase, keeping the text between them.
+    pub fn map_words<F>(&self, phrase: &'_ str, rule: F) -> String
+    where
+        F: Fn(&str) -> String,
+    {
+        let (separators, words) = self.split_phrase(phrase);
+        let words = words.iter().map(|word| rule(word)).collect::<Vec<_>>();
+        interleave(&separators, &words)
+    }
+
+    /// Phonetic representation of each word of a phrase.
+    pub fn phrase_representation(&self, phrase: &'_ str, transcription: Transcription) -> String {
+        self.map_words(phrase, |word| {
+            self.phonetic_representation(word, transcription)
+        })
+    }
+
+    /// Spelling of each word of a phrase, none when the orthography does not exist.
+    pub fn spell_phrase(&self, phrase: &'_ str, name: &'_ str) -> Option<String> {
+        self.orthography(name)?;
+        Some(self.map_words(phrase, |word| self.spell(word, name).unwrap_or_default()))
+    }
+
+    /// Text around the words and the words of a phrase, the text being
+    /// one more than the words. Punctuation used in a letter is part of the words,
+    /// a `!name` marker is part of the text.
+    fn split_phrase(&self, phrase: &'_ str) -> (Vec<String>, Vec<String>) {
+        let letters = self.segment_letters();
+        let is_separator = |c: char| {
+            (c.is_whitespace()
+                || c.is_ascii_punctuation() && c != MORPHEME_BOUNDARY && c != WORD_BOUNDARY
+                || PUNCTUATION.contains(c))
+                && !letters.iter().any(|letter| letter.contains(c))
+        };
+        let mut separators = vec![String::new()];
+        let mut words: Vec<String> = vec![];
+        let mut in_word = false;
+        let mut in_marker = false;
+        phrase.char_indices().for_each(|(index, c)| {
+            if c.is_whitespace() {
+                in_marker = false;
+            } else if !in_word && !in_marker && c == '!' {
+                let part = phrase[index..].split(char::is_whitespace).next();
+                in_marker = part.map(is_marker).unwrap_or(false);
+            }
+            if in_marker || is_separator(c) {
+                if in_word {
+                    separators.push(String::new());
+                    in_word = false;
+                }
+                if let Some(separator) = separators.last_mut() {
+                    separator.push(c);
+                }
+            } else {
+                if !in_word {
+                    words.push(String::new());
+                    in_word = true;
+                }
+                if let Some(word) = words.last_mut() {
+                    word.push(c);
+                }
+            }
+        });
+        if in_word {
+            separators.push(String::new());
+        }
+        (separators, words)
+    }
+}
+
+/// Whether the pattern has a word boundary between two letters,
+/// the rule then applying to the whole phrase.
+pub(super) fn crosses_words(pattern: &[Pattern]) -> bool {
+    pattern.iter().any(|part| match part {
+        Pattern::WordBoundary => true,
+        Pattern::Optional(patterns) | Pattern::Repeat(patterns) | Pattern::Input(patterns) => {
+            crosses_words(patterns)
+        }
+        Pattern::Alternation(alternatives) => alternatives
+            .iter()
+            .any(|alternative| crosses_words(alternative)),
+        _ => false,
+    })
+}
+
+/// Applies `rule` to the phrase when it crosses the words, to each word otherwise,
+/// the `blocked` words being kept as they are.
+pub(super) fn each_word<F>(phrase: &'_ str, (crossing, blocked): (bool, &[bool]), rule: F) -> String
+where
+    F: Fn(&str) -> String,
+{
+    let is_blocked = |index: usize| blocked.get(index).copied().unwrap_or(false);
+    let words = phrase.split(WORD_BOUNDARY).collect::<Vec<_>>();
+    let output = if crossing {
+        let output = rule(phrase);
+        let changed = output.split(WORD_BOUNDARY).collect::<Vec<_>>();
+        if changed.len() != words.len() {
+            return output;
+        }
+        changed
+            .iter()
+            .enumerate()
+            .map(|(index, word)| {
+                if is_blocked(index) {
+                    words[index]
+                } else {
+                    word
+                }
+            })
+            .map(str::to_string)
+            .collect::<Vec<_>>()
+    } else {
+        words
+            .iter()
+            .enumerate()
+            .map(|(index, word)| {
+                if is_blocked(index) {
+                    word.to_string()
+                } else {
+                    rule(word)
+                }
+            })
+            .collect()
+    };
+    output.join(&WORD_BOUNDARY.to_string())
+}
+
+/// Puts the text of the phrase back around the words the rules gave.
+fn join_phrase(separators: &[String], words: &'_ str) -> String {
+    let words = words
+        .split(WORD_BOUNDARY)
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    if separators.len() > 1 && words.len() + 1 != separators.len() {
+        warn!("The rules changed the words of {}", words.join(" "));
+        return words.join(" ");
+    }
+    interleave(separators, &words)
+}
+
+fn interleave(separators: &[String], words: &[String]) -> String {
+    let mut result = separators.first().cloned().unwrap_or_default();
+    words
+        .iter()
+        .zip(separators.iter().skip(1))
+        .for_each(|(word, separator)| {
+            result.push_str(word);
+            result.push_str(separator);
+        });
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    fn transform(rules: &str, phrase: &str) -> String {
+        let input = format!(
+            "letters: a, e, i, o, u, l, m, n, s, z, t, k, p\nrules:\n{}\n",
+            rules
+        );
+        crate::sound_system::from_string(&input)
+            .unwrap()
+            .phrase_transformation(vec![phrase.to_string()], None, None)
+            .unwrap()
+            .output
+            .remove(0)
+            .remove(0)
+    }
+
+    #[test]
+    fn test_word_edges() {
+        assert_eq!(
+            transform("_#: s -> z", "les amis, les tous."),
+            "lez amiz, lez touz."
+        );
+        assert_eq!(transform("#_: t -> k", "tata « tu »"), "kata « ku »");
+    }
+
+    #[test]
+    fn test_markers() {
+        let rules = "lenition = V_V: p -> b";
+        assert_eq!(transform(rules, "apa, apa !lenition"), "apa, apa");
+        assert_eq!(transform(rules, "apa, apa !"), "aba, aba !");
+        let sound_system = crate::sound_system::from_string("letters: a, p\n").unwrap();
+        assert_eq!(
+            sound_system.map_words("apa !lenition apa", |word| word.to_uppercase()),
+            "APA !lenition APA"
+        );
+    }
+
+    #[test]
+    fn test_phrase_exceptions() {
+        assert_eq!(transform("V_V: p -> b except apa", "apa tapa"), "apa taba");
+        assert_eq!(
+            transform("V_V: p -> b except /^apa$/", "apa, tapa"),
+            "apa, taba"
+        );
+        assert_eq!(
+            transform("_#V: s -> z except les", "les amis tous amis"),
+            "les amis touz amis"
+        );
+    }
+
+    #[test]
+    fn test_external_sandhi() {
+        assert_eq!(
+            transform("_#V: s -> z", "les amis sont tous là"),
+            "lez amis sont tous là"
+        );
+        assert_eq!(
+            transform("_#V: e -> ", "le ami, le petit"),
+            "l ami, le petit"
+        );
+        assert_eq!(transform("n#_: t -> n", "un tour un pas"), "un nour un pas");
+    }
+}
@@ -0,0 +1,364 @@
+use super::matcher::Segments;
+use super::rules::{rewrite, rule_outputs};
+use super::{Pattern, PhonemeCondition, PhonemeDifference, Rule, SoundSystem};
+use std::cmp::Ordering;
+use std::ops::Range;
+
+/// Proto-form of a word.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Reconstruction {
+    pub word: String,
+    /// Share of its syllables allowed by `syllables:`, from 0 to 1.
+    pub fit: f64,
+}
+
+/// Mergers multiply the proto-forms of a word, only the first ones are kept.
+const MAX_RECONSTRUCTIONS: usize = 64;
+/// Guard against inputs of rules expanding to too many letters.
+const MAX_INPUTS: usize = 256;
+
+impl SoundSystem {
+    /// Words the rules could have turned into `word`, going through
+    /// the rules backwards. A rule merging several letters gives
+    /// several proto-forms, the ones fitting the syllables of the
+    /// language best come first, then the shortest.
+    pub fn reconstruct(&mut self, word: &'_ str) -> Vec<Reconstruction> {
+        let rules = self.rules().clone();
+        let mut previous_phonemes = vec![];
+        rules.iter().for_each(|rule| {
+            if let Rule::PhonemeRule {
+                phoneme_differences,
+                ..
+            } = rule
+            {
+                previous_phonemes.push(self.previous_phonemes(phoneme_differences));
+                self.update_phoneme(phoneme_differences);
+            }
+        });
+        let mut words = vec![word.to_string()];
+        rules.iter().rev().for_each(|rule| {
+            if let Rule::PhonemeRule { .. } = rule {
+                let previous = previous_phonemes.pop().unwrap_or_default();
+                previous.into_iter().rev().for_each(|(letter, phonemes)| {
+                    match phonemes {
+                        Some(phonemes) => self.phonemes().insert(letter, phonemes),
+                        None => self.phonemes().remove(&letter),
+                    };
+                });
+                self.sort_phonemes();
+                return;
+            }
+            let mut result: Vec<String> = vec![];
+            words.iter().for_each(|current| {
+                self.unapply(rule, current)
+                    .into_iter()
+                    .filter(|candidate| rule_outputs(self, rule, candidate).contains(current))
+                    .for_each(|candidate| {
+                        if !result.contains(&candidate) {
+                            result.push(candidate)
+                        }
+                    })
+            });
+            if result.len() > MAX_RECONSTRUCTIONS {
+                warn!(
+                    "Keeping only {} proto-forms of {}",
+                    MAX_RECONSTRUCTIONS, word
+                );
+                result.truncate(MAX_RECONSTRUCTIONS);
+            }
+            words = result;
+        });
+        let mut reconstructions = words
+            .into_iter()
+            .map(|word| Reconstruction {
+                fit: self.phonotactic_fit(&word),
+                word,
+            })
+            .collect::<Vec<_>>();
+        reconstructions.sort_by(|left, right| {
+            right
+                .fit
+                .partial_cmp(&left.fit)
+                .unwrap_or(Ordering::Equal)
+                .then(left.word.chars().count().cmp(&right.word.chars().count()))
+        });
+        reconstructions
+    }
+
+    fn previous_phonemes(
+        &mut self,
+        differences: &[PhonemeDifference],
+    ) -> Vec<(String, Option<Vec<PhonemeCondition>>)> {
+        differences
+            .iter()
+            .filter_map(|difference| match difference {
+                PhonemeDifference::Skip => None,
+                PhonemeDifference::Delete(letter) | PhonemeDifference::Upsert(letter, _) => {
+                    Some((letter.clone(), self.phonemes().get(letter).cloned()))
+                }
+            })
+            .collect()
+    }
+
+    /// Candidates for the words `rule` turned into `word`,
+    /// the word itself included.
+    fn unapply(&self, rule: &Rule, word: &'_ str) -> Vec<String> {
+        match rule {
+            Rule::SoundRule {
+                pattern,
+                replacement,
+                ..
+            }
+            | Rule::SyllableRule {
+                pattern,
+                replacement,
+                ..
+            } => {
+                let input = pattern
+                    .iter()
+                    .find_map(|part| match part {
+                        Pattern::Input(input) => Some(input.as_slice()),
+                        _ => None,
+                    })
+                    .unwrap_or_default();
+                let rewrites = expand(input)
+                    .into_iter()
+                    .filter_map(|letters| {
+                        rewrite(self, input, replacement.as_ref(), &letters)
+                            .map(|output| (letters, output))
+                    })
+                    .filter(|(letters, output)| letters != output)
+                    .collect::<Vec<_>>();
+                self.unrewrite(&rewrites, word)
+            }
+            Rule::ToneRule { input, output, .. } => {
+                self.untone((input.as_str(), output.as_str()), word)
+            }
+            Rule::ReduplicationRule { reduplication, .. } => {
+                let segments = self.segments(word);
+                let mut candidates = vec![word.to_string()];
+                (1..segments.len()).for_each(|length| {
+                    let rest = if reduplication.prefixed {
+                        &segments[length..]
+                    } else {
+                        &segments[..segments.len() - length]
+                    };
+                    candidates.push(rest.iter().map(|(letter, _)| letter.as_str()).collect());
+                });
+                candidates
+            }
+            Rule::PhonemeRule { .. } => vec![word.to_string()],
+        }
+    }
+
+    /// Puts back the inputs of `rewrites` in place of their outputs,
+    /// at every combination of their positions in the word.
+    fn unrewrite(&self, rewrites: &[(String, String)], word: &'_ str) -> Vec<String> {
+        let letters = self.segment_letters();
+        let segments = Segments::new(word, &letters);
+        let mut occurrences: Vec<(Range<usize>, &str)> = vec![];
+        rewrites.iter().for_each(|(input, output)| {
+            let output = Segments::new(output, &letters);
+            let size = output.len();
+            (0..=segments.len())
+                .filter(|start| start + size <= segments.len())
+                .filter(|start| {
+                    (0..size).all(|index| segments.get(start + index) == output.get(index))
+                })
+                .for_each(|start| occurrences.push((start..start + size, input)));
+        });
+        occurrences.sort_by_key(|(range, _)| (range.start, range.end));
+        let mut result = vec![];
+        combine(
+            &segments,
+            &occurrences,
+            (0, 0, false),
+            &mut vec![],
+            &mut result,
+        );
+        result
+    }
+
+    /// Tones the tone rule changed back to its input.
+    fn untone(&self, (input, output): (&'_ str, &'_ str), word: &'_ str) -> Vec<String> {
+        let syllables = self.prosodic_syllables(word);
+        let changed = syllables
+            .iter()
+            .enumerate()
+            .filter(|(_, (_, _, tone))| tone.map(|toneme| toneme.name == output).unwrap_or(false))
+            .map(|(index, _)| index)
+            .collect::<Vec<_>>();
+        let toneme = self.tone(input);
+        let mut result = vec![];
+        for subset in 0..(1usize << changed.len().min(8)) {
+            result.push(
+                syllables
+                    .iter()
+                    .enumerate()
+                    .map(|(index, (syllable, stress, tone))| {
+                        let letters = syllable
+                            .iter()
+                            .map(|(letter, _)| letter.as_str())
+                            .collect::<String>();
+                        let tone = match changed.iter().position(|other| *other == index) {
+                            Some(position) if subset & (1 << position) != 0 => toneme,
+                            _ => *tone,
+                        };
+                        self.write_syllable(*stress, &letters, tone)
+                    })
+                    .collect(),
+            );
+        }
+        result
+    }
+
+    /// Share of the syllables of `word` matching the `syllables:` patterns.
+    fn phonotactic_fit(&self, word: &'_ str) -> f64 {
+        let syllables = self.syllabify_segments(word);
+        if self.syllables().is_empty() || syllables.is_empty() {
+            return 1.0;
+        }
+        let fitting = syllables
+            .iter()
+            .filter(|syllable| self.is_syllable(syllable))
+            .count();
+        fitting as f64 / syllables.len() as f64
+    }
+}
+
+/// Every string of letters the input of a rule matches,
+/// a repetition counting once.
+fn expand(patterns: &[Pattern]) -> Vec<String> {
+    patterns
+        .iter()
+        .fold(vec![String::new()], |result, pattern| {
+            let choices = match pattern {
+                Pattern::Letter(letter) => vec![letter.clone()],
+                Pattern::Letters(letters, _) => letters.clone(),
+                Pattern::Alternation(alternatives) => alternatives
+                    .iter()
+                    .flat_map(|other| expand(other))
+                    .collect(),
+                Pattern::Optional(optional) => {
+                    let mut choices = vec![String::new()];
+                    choices.extend(expand(optional));
+                    choices
+                }
+                Pattern::Repeat(repeated) | Pattern::Input(repeated) => expand(repeated),
+                Pattern::Start
+                | Pattern::End
+                | Pattern::SyllableBoundary
+                | Pattern::MorphemeBoundary
+                | Pattern::WordBoundary
+                | Pattern::Gap => {
+                    vec![String::new()]
+                }
+            };
+            result
+                .iter()
+                .flat_map(|start| {
+                    choices
+                        .iter()
+                        .map(move |choice| format!("{}{}", start, choice))
+                })
+                .take(MAX_INPUTS)
+                .collect()
+        })
+}
+
+/// Builds the words with the occurrences from `index` not overlapping
+/// the ones already chosen. Two insertions are never at the same place.
+fn combine<'a>(
+    segments: &Segments<'_>,
+    occurrences: &[(Range<usize>, &'a str)],
+    (index, end, inserted): (usize, usize, bool),
+    chosen: &mut Vec<(Range<usize>, &'a str)>,
+    result: &mut Vec<String>,
+) {
+    if result.len() >= MAX_INPUTS {
+        return;
+    }
+    if index == occurrences.len() {
+        let mut word = String::new();
+        let mut position = 0;
+        chosen.iter().for_each(|(range, input)| {
+            word.push_str(segments.text(&(position..range.start)));
+            word.push_str(input);
+            position = range.end;
+        });
+        word.push_str(segments.text(&(position..segments.len())));
+        result.push(word);
+        return;
+    }
+    combine(
+        segments,
+        occurrences,
+        (index + 1, end, inserted),
+        chosen,
+        result,
+    );
+    let (range, input) = &occurrences[index];
+    let is_insertion = range.is_empty();
+    let after = range.start > end || (range.start == end && !(inserted && is_insertion));
+    if after {
+        chosen.push((range.clone(), input));
+        combine(
+            segments,
+            occurrences,
+            (index + 1, range.end, is_insertion),
+            chosen,
+            result,
+        );
+        chosen.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    fn reconstruct(rules: &str, word: &str) -> Vec<(String, f64)> {
+        let input = format!(
+            "letters: a, i, p, t, b, d\nsyllables: C?V\nrules:\n{}\n",
+            rules
+        );
+        crate::sound_system::from_string(&input)
+            .unwrap()
+            .reconstruct(word)
+            .into_iter()
+            .map(|reconstruction| (reconstruction.word, reconstruction.fit))
+            .collect()
+    }
+
+    #[test]
+    fn test_mergers() {
+        assert_eq!(
+            reconstruct("V_V: p -> b", "aba"),
+            vec![("aba".to_string(), 1.0), ("apa".to_string(), 1.0)]
+        );
+        assert_eq!(
+            reconstruct("V_V: p -> b", "bab"),
+            vec![("bab".to_string(), 0.0)]
+        );
+    }
+
+    #[test]
+    fn test_deletions() {
+        assert_eq!(
+            reconstruct("_#: t -> ", "pa"),
+            vec![("pa".to_string(), 1.0), ("pat".to_string(), 0.0)]
+        );
+        assert_eq!(
+            reconstruct("V_V: p -> b\n_#: t -> ", "aba"),
+            vec![
+                ("aba".to_string(), 1.0),
+                ("apa".to_string(), 1.0),
+                ("abat".to_string(), 0.5),
+                ("apat".to_string(), 0.5)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_unreachable() {
+        assert!(reconstruct("_: d -> t", "da").is_empty());
+    }
+}
@@ -0,0 +1,145 @@
+use super::{Reduplicant, Reduplication, Segment, ShapePart, SoundSystem};
+
+impl SoundSystem {
+    /// Word with the copy of its reduplicant, none when the word
+    /// does not have it, like a word starting with a vowel for `[CV]-`.
+    pub fn reduplicate(&self, word: &'_ str, reduplication: &Reduplication) -> Option<String> {
+        let copy = self.reduplicant(word, reduplication)?;
+        if reduplication.prefixed {
+            Some(format!("{}{}", copy, word))
+        } else {
+            Some(format!("{}{}", word, copy))
+        }
+    }
+
+    /// Part of the word copied, taken from its beginning for a prefixed
+    /// reduplication and from its end otherwise.
+    pub fn reduplicant(&self, word: &'_ str, reduplication: &Reduplication) -> Option<String> {
+        if word.is_empty() {
+            return None;
+        }
+        match &reduplication.reduplicant {
+            Reduplicant::Word => Some(word.to_string()),
+            Reduplicant::Syllable => {
+                let syllables = self.syllabify_segments(word);
+                let syllable = if reduplication.prefixed {
+                    syllables.first()
+                } else {
+                    syllables.last()
+                }?;
+                Some(letters(syllable))
+            }
+            Reduplicant::Shape(parts) => {
+                let slots = parts
+                    .iter()
+                    .flat_map(|part| match part {
+                        ShapePart::Class(class) => vec![(Some(class), None)],
+                        ShapePart::Fixed(fixed) => self
+                            .segments(fixed)
+                            .into_iter()
+                            .map(|(letter, _)| (None, Some(letter)))
+                            .collect(),
+                    })
+                    .collect::<Vec<_>>();
+                let segments = self.segments(word);
+                if slots.len() > segments.len() {
+                    return None;
+                }
+                let base = if reduplication.prefixed {
+                    &segments[..slots.len()]
+                } else {
+                    &segments[segments.len() - slots.len()..]
+                };
+                slots
+                    .iter()
+                    .zip(base.iter())
+                    .map(|(slot, segment)| match slot {
+                        (Some(class), _) if self.segment_in_class(segment, class) => {
+                            Some(segment.0.clone())
+                        }
+                        (Some(_), _) => None,
+                        (None, fixed) => fixed.clone(),
+                    })
+                    .collect()
+            }
+        }
+    }
+}
+
+fn letters(segments: &[Segment]) -> String {
+    segments.iter().map(|(letter, _)| letter.as_str()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sound_system() -> SoundSystem {
+        crate::sound_system::from_string(
+            "letters: a, i, u, e, k, t, p, m, n\nsyllables: CV CVC\n\
+             rules:\nplural = reduplicate [σ]- except kata\n",
+        )
+        .unwrap()
+    }
+
+    fn reduplicate(reduplicant: Reduplicant, prefixed: bool, word: &str) -> Option<String> {
+        sound_system().reduplicate(
+            word,
+            &Reduplication {
+                reduplicant,
+                prefixed,
+            },
+        )
+    }
+
+    fn shape(parts: &[&str]) -> Reduplicant {
+        Reduplicant::Shape(
+            parts
+                .iter()
+                .map(|part| {
+                    if part.chars().all(char::is_uppercase) {
+                        ShapePart::Class(part.to_string())
+                    } else {
+                        ShapePart::Fixed(part.to_string())
+                    }
+                })
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_reduplicants() {
+        assert_eq!(
+            reduplicate(Reduplicant::Word, true, "kata"),
+            Some("katakata".to_string())
+        );
+        assert_eq!(
+            reduplicate(Reduplicant::Syllable, true, "pantu"),
+            Some("panpantu".to_string())
+        );
+        assert_eq!(
+            reduplicate(Reduplicant::Syllable, false, "kata"),
+            Some("katata".to_string())
+        );
+        assert_eq!(
+            reduplicate(shape(&["C", "V"]), true, "pantu"),
+            Some("papantu".to_string())
+        );
+        assert_eq!(
+            reduplicate(shape(&["C", "e"]), true, "kata"),
+            Some("kekata".to_string())
+        );
+        assert_eq!(reduplicate(shape(&["C", "V"]), true, "akta"), None);
+    }
+
+    #[test]
+    fn test_reduplication_rule() {
+        let output = sound_system()
+            .sound_trasformation(vec!["pita".to_string(), "kata".to_string()])
+            .output;
+        assert_eq!(
+            output,
+            vec![vec!["pipita".to_string()], vec!["kata".to_string()]]
+        );
+    }
+}
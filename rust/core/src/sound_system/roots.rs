@@ -0,0 +1,236 @@
+use super::distribution;
+use super::{Distribution, RootException, SoundSystem, TemplatePart};
+
+/// Generation gives up on a root after this many attempts.
+const MAX_ATTEMPTS: usize = 100;
+
+/// Word of a root given by a template.
+#[derive(Debug, Serialize, PartialEq)]
+pub struct Derivation {
+    pub template: String,
+    /// Word before the rules.
+    pub word: String,
+    /// Variants of the word after the rules.
+    pub forms: Vec<String>,
+}
+
+impl SoundSystem {
+    /// Roots following the `roots:` patterns, a consonant of each class,
+    /// none of them matching a `roots except:` exception. A pattern with
+    /// a class without letters is ignored.
+    pub fn generate_roots(&self, number: usize) -> Vec<Vec<String>> {
+        let patterns = self
+            .roots()
+            .patterns
+            .iter()
+            .filter(|pattern| {
+                match pattern
+                    .iter()
+                    .find(|class| self.class_distribution(class).is_empty())
+                {
+                    Some(class) => {
+                        warn!(
+                            "Ignoring the root pattern {}: no letters in {}",
+                            pattern.join(""),
+                            class
+                        );
+                        false
+                    }
+                    None => true,
+                }
+            })
+            .collect::<Vec<_>>();
+        let mut roots: Vec<Vec<String>> = vec![];
+        if patterns.is_empty() {
+            warn!("No roots to generate without a roots: section");
+            return roots;
+        }
+        for _ in 0..number {
+            let root = (0..MAX_ATTEMPTS)
+                .filter_map(|_| self.root(patterns[distribution::power_law(patterns.len(), 0.5)]))
+                .find(|root| self.is_allowed_root(root) && !roots.contains(root));
+            match root {
+                Some(root) => roots.push(root),
+                None => warn!("No new root after {} attempts", MAX_ATTEMPTS),
+            }
+        }
+        roots
+    }
+
+    /// Root with a letter of each class, none when a class has no letters.
+    fn root(&self, pattern: &[String]) -> Option<Vec<String>> {
+        pattern
+            .iter()
+            .map(|class| {
+                let distribution = self.class_distribution(class);
+                if distribution.is_empty() {
+                    None
+                } else {
+                    Some(distribution::select(&distribution).to_string())
+                }
+            })
+            .collect()
+    }
+
+    fn class_distribution(&self, class: &'_ str) -> Vec<Distribution> {
+        match self.classes().get(class) {
+            Some(letters) => self
+                .distribution()
+                .iter()
+                .filter(|(letter, _)| letters.contains(letter))
+                .cloned()
+                .collect(),
+            None => vec![],
+        }
+    }
+
+    /// Whether no `roots except:` exception matches the root.
+    pub fn is_allowed_root(&self, root: &[String]) -> bool {
+        let in_class = |class: &String, letter: &String| {
+            self.classes()
+                .get(class)
+                .map(|letters| letters.contains(letter))
+                .unwrap_or(false)
+        };
+        self.roots()
+            .exceptions
+            .iter()
+            .all(|exception| match exception {
+                RootException::Identical(left, right) => {
+                    match (root.get(*left), root.get(*right)) {
+                        (Some(left), Some(right)) => left != right,
+                        _ => true,
+                    }
+                }
+                RootException::Adjacent(left, right) => !root
+                    .windows(2)
+                    .any(|pair| in_class(left, &pair[0]) && in_class(right, &pair[1])),
+            })
+    }
+
+    /// Word of each template with as many radicals as the root,
+    /// with the name of the template.
+    pub fn derive(&self, root: &[String]) -> Vec<(String, String)> {
+        self.roots()
+            .templates
+            .iter()
+            .filter(|template| {
+                template
+                    .parts
+                    .iter()
+                    .filter_map(|part| match part {
+                        TemplatePart::Radical(position) => Some(position + 1),
+                        TemplatePart::Letters(_) => None,
+                    })
+                    .max()
+                    == Some(root.len())
+            })
+            .map(|template| {
+                let word = template
+                    .parts
+                    .iter()
+                    .map(|part| match part {
+                        TemplatePart::Letters(letters) => letters.as_str(),
+                        TemplatePart::Radical(position) => root[*position].as_str(),
+                    })
+                    .collect();
+                (template.name.clone(), word)
+            })
+            .collect()
+    }
+
+    /// Words of every root, going through the rules.
+    pub fn derive_words(&mut self, roots: &[Vec<String>]) -> Vec<Vec<Derivation>> {
+        let derived = roots
+            .iter()
+            .map(|root| self.derive(root))
+            .collect::<Vec<_>>();
+        let words = derived
+            .iter()
+            .flatten()
+            .map(|(_, word)| word.clone())
+            .collect();
+        let mut output = self.sound_trasformation(words).output.into_iter();
+        derived
+            .into_iter()
+            .map(|derivations| {
+                derivations
+                    .into_iter()
+                    .map(|(template, word)| Derivation {
+                        template,
+                        word,
+                        forms: output.next().unwrap_or_default(),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sound_system() -> SoundSystem {
+        crate::sound_system::from_string(
+            "letters: a, i, u, k, t, b, m, l, s\n\
+             P = b m\n\
+             roots: CCC CCCC\n\
+             roots except: 1 = 2, P P\n\
+             templates:\n perfective: C1aC2aC3\n agent: C1aaC2iC3\n place: maC1C2aC3\n\
+             \x20quadriliteral: C1aC2C3aC4\n\
+             rules:\n_#: b -> p\n",
+        )
+        .unwrap()
+    }
+
+    fn root(letters: &[&str]) -> Vec<String> {
+        letters.iter().map(|letter| letter.to_string()).collect()
+    }
+
+    #[test]
+    fn test_roots() {
+        let sound_system = sound_system();
+        assert!(sound_system.is_allowed_root(&root(&["k", "t", "b"])));
+        assert!(!sound_system.is_allowed_root(&root(&["k", "k", "b"])));
+        assert!(!sound_system.is_allowed_root(&root(&["k", "m", "b"])));
+        assert!(sound_system.is_allowed_root(&root(&["b", "k", "m"])));
+        sound_system.generate_roots(20).iter().for_each(|root| {
+            assert!(root.len() == 3 || root.len() == 4);
+            assert!(sound_system.is_allowed_root(root));
+        });
+    }
+
+    #[test]
+    fn test_root_pattern_without_letters() {
+        let sound_system =
+            crate::sound_system::from_string("letters: a, k, t, b\nP = p\nroots: CCC CPC\n")
+                .unwrap();
+        let roots = sound_system.generate_roots(10);
+        assert!(!roots.is_empty());
+        assert!(roots.iter().all(|root| root.len() == 3));
+        assert!(roots.iter().all(|root| !root.contains(&"p".to_string())));
+    }
+
+    #[test]
+    fn test_templates() {
+        let mut sound_system = sound_system();
+        assert_eq!(
+            sound_system.derive(&root(&["k", "t", "b"])),
+            vec![
+                ("perfective".to_string(), "katab".to_string()),
+                ("agent".to_string(), "kaatib".to_string()),
+                ("place".to_string(), "maktab".to_string())
+            ]
+        );
+        let derivations = sound_system.derive_words(&[root(&["s", "l", "t", "b"])]);
+        assert_eq!(
+            derivations[0],
+            vec![Derivation {
+                template: "quadriliteral".to_string(),
+                word: "saltab".to_string(),
+                forms: vec!["saltap".to_string()],
+            }]
+        );
+    }
+}
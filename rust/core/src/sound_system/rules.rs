@@ -1,140 +1,964 @@
-use super::{Rule, SoundSystem};
-use regex::{Captures, Regex};
+use super::matcher::{self, group_class, Found, Segments, INPUT, SYLLABLE};
+use super::phrase::{crosses_words, each_word, WORD_BOUNDARY};
+use super::syllable::syllable_parts;
+use super::{
+    Exceptions, Pattern, Rule, RuleApplication, RuleMode, Segment, SoundSystem, SyllablePosition,
+};
+use rand::prelude::*;
+use regex::Regex;
+use std::ops::Range;
+use std::str::FromStr;
 
 #[derive(Debug, Default, Serialize, Eq, PartialEq)]
 pub struct Transformation {
-    pub output: Vec<String>,
+    /// Variants of each word.
+    pub output: Vec<Vec<String>>,
     pub history: Vec<History>,
 }
 
 #[derive(Debug, Serialize, Eq, PartialEq)]
 pub struct History {
     pub rule: String,
-    pub words: Vec<String>,
+    pub words: Vec<Vec<String>>,
+    pub stage: Option<String>,
+    /// Words escaping the rule through its exceptions.
+    pub blocked: Vec<bool>,
+}
+
+impl Transformation {
+    /// Words at the end of each stage.
+    pub fn stages(&self) -> Vec<(&str, &Vec<Vec<String>>)> {
+        let mut result: Vec<(&str, &Vec<Vec<String>>)> = vec![];
+        self.history.iter().for_each(|history| {
+            if let Some(stage) = &history.stage {
+                match result.last_mut() {
+                    Some((name, words)) if name == stage => *words = &history.words,
+                    _ => result.push((stage, &history.words)),
+                }
+            }
+        });
+        result
+    }
 }
 
 impl SoundSystem {
     pub fn sound_trasformation(&mut self, words: Vec<String>) -> Transformation {
+        let size = self.rules().len();
+        self.apply_rules(words, 0..size)
+    }
+
+    /// Applies the rules from the beginning of the stage `from`
+    /// to the end of the stage `until`.
+    pub fn stage_transformation(
+        &mut self,
+        words: Vec<String>,
+        from: Option<&'_ str>,
+        until: Option<&'_ str>,
+    ) -> Result<Transformation, &'static str> {
+        let start = match from {
+            Some(name) => self.stage(name).ok_or("unknown stage")?.rules.start,
+            None => 0,
+        };
+        let end = match until {
+            Some(name) => self.stage(name).ok_or("unknown stage")?.rules.end,
+            None => self.rules().len(),
+        };
+        // The words are already at `from`, only the phonemes have to change.
+        self.rules().clone()[..start].iter().for_each(|rule| {
+            if let Rule::PhonemeRule {
+                phoneme_differences,
+                ..
+            } = rule
+            {
+                self.update_phoneme(phoneme_differences)
+            }
+        });
+        Ok(self.apply_rules(words, start..end.max(start)))
+    }
+
+    fn apply_rules(&mut self, words: Vec<String>, range: Range<usize>) -> Transformation {
         let mut history: Vec<History> = vec![];
-        let output = self
-            .rules()
-            .clone()
+        let rules = self.rules().clone();
+        let inputs = words
             .iter()
-            .fold(words, |words, rule| match rule {
+            .map(|word| split_markers(word))
+            .collect::<Vec<_>>();
+        let words = inputs.iter().map(|(word, _)| vec![word.clone()]).collect();
+        let mut rng = thread_rng();
+        let output = range.fold(words, |words: Vec<Vec<String>>, index| {
+            let (name, blocked, output) = match &rules[index] {
                 Rule::SoundRule {
                     name,
-                    regex,
+                    pattern,
+                    unless,
                     replacement,
+                    mode,
+                    application,
+                    exceptions,
                 } => {
-                    let regex = Regex::new(&regex).unwrap();
-                    let output = words
-                        .iter()
-                        .map(|word| apply_sound_rule(self, &regex, replacement.as_ref(), word))
-                        .collect::<Vec<_>>();
-                    history.push(History {
-                        rule: name.to_string(),
-                        words: output.clone(),
-                    });
-
-                    output
+                    let matcher = Matcher::new(self, pattern, unless.as_ref());
+                    let blocked = blocked_words(exceptions, &inputs);
+                    let crossing = crosses_words(pattern);
+                    let output = apply_to_variants(
+                        &words,
+                        (*application, &blocked),
+                        &mut rng,
+                        |phrase, mask| {
+                            each_word(phrase, (crossing, mask), |word| {
+                                apply_sound_rule(self, *mode, &matcher, replacement.as_ref(), word)
+                            })
+                        },
+                    );
+                    (name, blocked, output)
+                }
+                Rule::SyllableRule {
+                    name,
+                    position,
+                    pattern,
+                    unless,
+                    replacement,
+                    mode,
+                    application,
+                    exceptions,
+                } => {
+                    let matcher = Matcher::new(self, pattern, unless.as_ref());
+                    let blocked = blocked_words(exceptions, &inputs);
+                    let output = apply_to_variants(
+                        &words,
+                        (*application, &blocked),
+                        &mut rng,
+                        |phrase, mask| {
+                            each_word(phrase, (false, mask), |word| {
+                                apply_syllable_rule(
+                                    self,
+                                    (*position, *mode),
+                                    &matcher,
+                                    replacement.as_ref(),
+                                    word,
+                                )
+                            })
+                        },
+                    );
+                    (name, blocked, output)
+                }
+                Rule::ToneRule {
+                    name,
+                    before,
+                    input,
+                    after,
+                    output,
+                    application,
+                    exceptions,
+                } => {
+                    let blocked = blocked_words(exceptions, &inputs);
+                    let output = apply_to_variants(
+                        &words,
+                        (*application, &blocked),
+                        &mut rng,
+                        |phrase, mask| {
+                            each_word(phrase, (false, mask), |word| {
+                                apply_tone_rule(self, (before, input, after), output, word)
+                            })
+                        },
+                    );
+                    (name, blocked, output)
+                }
+                Rule::ReduplicationRule {
+                    name,
+                    reduplication,
+                    application,
+                    exceptions,
+                } => {
+                    let blocked = blocked_words(exceptions, &inputs);
+                    let output = apply_to_variants(
+                        &words,
+                        (*application, &blocked),
+                        &mut rng,
+                        |phrase, mask| {
+                            each_word(phrase, (false, mask), |word| {
+                                self.reduplicate(word, reduplication)
+                                    .unwrap_or_else(|| word.to_string())
+                            })
+                        },
+                    );
+                    (name, blocked, output)
                 }
                 Rule::PhonemeRule {
                     name,
                     phoneme_differences,
                 } => {
                     self.update_phoneme(phoneme_differences);
-                    history.push(History {
-                        rule: name.to_string(),
-                        words: words.clone(),
-                    });
-                    words
+                    (name, vec![vec![]; words.len()], words)
                 }
+            };
+            history.push(History {
+                rule: name.to_string(),
+                words: output.clone(),
+                blocked: blocked
+                    .iter()
+                    .map(|mask| !mask.is_empty() && mask.iter().all(|blocked| *blocked))
+                    .collect(),
+                stage: self
+                    .stages()
+                    .iter()
+                    .find(|stage| stage.rules.contains(&index))
+                    .map(|stage| stage.name.clone()),
             });
+            output
+        });
+        let output = output
+            .into_iter()
+            .map(|variants| {
+                variants
+                    .iter()
+                    .map(|word| self.remove_boundaries(word))
+                    .collect()
+            })
+            .collect();
         Transformation { output, history }
     }
 }
 
-fn apply_sound_rule(
+/// Words `rule` can turn `word` into: the changed word,
+/// and the word itself when the rule may not apply.
+pub(super) fn rule_outputs(sound_system: &SoundSystem, rule: &Rule, word: &'_ str) -> Vec<String> {
+    let (changed, application) = match rule {
+        Rule::SoundRule {
+            pattern,
+            unless,
+            replacement,
+            mode,
+            application,
+            ..
+        } => {
+            let matcher = Matcher::new(sound_system, pattern, unless.as_ref());
+            (
+                apply_sound_rule(sound_system, *mode, &matcher, replacement.as_ref(), word),
+                application,
+            )
+        }
+        Rule::SyllableRule {
+            position,
+            pattern,
+            unless,
+            replacement,
+            mode,
+            application,
+            ..
+        } => {
+            let matcher = Matcher::new(sound_system, pattern, unless.as_ref());
+            (
+                apply_syllable_rule(
+                    sound_system,
+                    (*position, *mode),
+                    &matcher,
+                    replacement.as_ref(),
+                    word,
+                ),
+                application,
+            )
+        }
+        Rule::ToneRule {
+            before,
+            input,
+            after,
+            output,
+            application,
+            ..
+        } => (
+            apply_tone_rule(sound_system, (before, input, after), output, word),
+            application,
+        ),
+        Rule::ReduplicationRule {
+            reduplication,
+            application,
+            ..
+        } => (
+            sound_system
+                .reduplicate(word, reduplication)
+                .unwrap_or_else(|| word.to_string()),
+            application,
+        ),
+        Rule::PhonemeRule { .. } => return vec![word.to_string()],
+    };
+    match application {
+        RuleApplication::Always => vec![changed],
+        _ => vec![word.to_string(), changed],
+    }
+}
+
+/// What the letters of the input of a rule become, leaving out its environment.
+pub(super) fn rewrite(
+    sound_system: &SoundSystem,
+    input: &[Pattern],
+    replacement: Option<&String>,
+    letters: &'_ str,
+) -> Option<String> {
+    let pattern = vec![Pattern::Start, Pattern::Input(input.to_vec()), Pattern::End];
+    let matcher = Matcher::new(sound_system, &pattern, None);
+    find_match(sound_system, &matcher, replacement, (letters, &[]), 0).map(|found| found.output)
+}
+
+/// Splits an input word from the `!name` markers of the rules it escapes,
+/// keeping the whitespace between the other parts of the input.
+pub(super) fn split_markers(word: &'_ str) -> (String, Vec<String>) {
+    let mut text = String::new();
+    let mut markers = vec![];
+    let mut rest = word.trim();
+    while !rest.is_empty() {
+        let start = rest
+            .find(|c: char| !c.is_whitespace())
+            .unwrap_or(rest.len());
+        let separator = &rest[..start];
+        rest = &rest[start..];
+        let end = rest.find(char::is_whitespace).unwrap_or(rest.len());
+        let part = &rest[..end];
+        rest = &rest[end..];
+        if is_marker(part) {
+            markers.push(part[1..].to_string());
+        } else {
+            if !text.is_empty() {
+                text.push_str(separator);
+            }
+            text.push_str(part);
+        }
+    }
+    (text, markers)
+}
+
+/// Whether a part of an input, between whitespace, is a `!name` marker.
+pub(super) fn is_marker(part: &'_ str) -> bool {
+    part.len() > 1 && part.starts_with('!')
+}
+
+/// Whether each word of each input escapes a rule, the words of a phrase
+/// being matched one by one against the exceptions.
+fn blocked_words(exceptions: &Exceptions, inputs: &[(String, Vec<String>)]) -> Vec<Vec<bool>> {
+    let pattern = exceptions
+        .pattern
+        .as_ref()
+        .map(|pattern| Regex::new(pattern).unwrap());
+    inputs
+        .iter()
+        .map(|(input, markers)| {
+            let marked = exceptions
+                .label
+                .as_ref()
+                .map(|label| {
+                    markers
+                        .iter()
+                        .any(|marker| marker.eq_ignore_ascii_case(label))
+                })
+                .unwrap_or(false);
+            input
+                .split(WORD_BOUNDARY)
+                .map(|word| {
+                    marked
+                        || exceptions.words.iter().any(|exception| exception == word)
+                        || pattern
+                            .as_ref()
+                            .map(|pattern| pattern.is_match(word))
+                            .unwrap_or(false)
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Applies `rule` to every variant of each word not blocked, `rng` deciding
+/// whether a probabilistic rule applies. Optional rules keep the unchanged
+/// variants next to the changed ones. The rule receives which words
+/// of a phrase are blocked.
+fn apply_to_variants<F, R>(
+    words: &[Vec<String>],
+    (application, blocked): (RuleApplication, &[Vec<bool>]),
+    rng: &mut R,
+    rule: F,
+) -> Vec<Vec<String>>
+where
+    F: Fn(&str, &[bool]) -> String,
+    R: Rng,
+{
+    words
+        .iter()
+        .zip(blocked)
+        .map(|(variants, blocked)| {
+            if !blocked.is_empty() && blocked.iter().all(|blocked| *blocked) {
+                return variants.clone();
+            }
+            let rule = |word: &'_ str| rule(word, blocked);
+            let mut result: Vec<String> = vec![];
+            variants.iter().for_each(|word| {
+                let outputs = match application {
+                    RuleApplication::Always => vec![rule(word)],
+                    RuleApplication::Probability(probability) if rng.gen_bool(probability) => {
+                        vec![rule(word)]
+                    }
+                    RuleApplication::Probability(_) => vec![word.clone()],
+                    RuleApplication::Optional => vec![word.clone(), rule(word)],
+                };
+                outputs.into_iter().for_each(|output| {
+                    if !result.contains(&output) {
+                        result.push(output)
+                    }
+                });
+            });
+            if result.len() > MAX_VARIANTS {
+                warn!(
+                    "Keeping only {} variants of {}",
+                    MAX_VARIANTS,
+                    variants.join(", ")
+                );
+                result.truncate(MAX_VARIANTS);
+            }
+            result
+        })
+        .collect()
+}
+
+fn apply_syllable_rule(
     sound_system: &SoundSystem,
-    rule: &Regex,
+    (position, mode): (SyllablePosition, RuleMode),
+    rule: &Matcher<'_>,
     replacement: Option<&String>,
     word: &'_ str,
 ) -> String {
-    let capture_names = rule
-        .capture_names()
-        .enumerate()
-        .filter_map(|(idx, c)| c.map(|c| (idx, c)))
+    let syllables = sound_system.prosodic_syllables(word);
+    let apply = |letters: &'_ str| apply_sound_rule(sound_system, mode, rule, replacement, letters);
+    match position {
+        SyllablePosition::Any => {
+            let mut boundaries = vec![];
+            let mut word = String::new();
+            syllables.iter().for_each(|(syllable, _, _)| {
+                if !word.is_empty() {
+                    boundaries.push(word.len());
+                }
+                word.push_str(&letters(syllable));
+            });
+            let (output, boundaries) = apply_sound_rule_to_syllables(
+                sound_system,
+                mode,
+                rule,
+                replacement,
+                (&word, boundaries),
+            );
+            let mut starts = vec![0];
+            starts.extend(boundaries);
+            starts.push(output.len());
+            starts
+                .windows(2)
+                .zip(syllables.iter())
+                .map(|(bounds, (_, stress, tone))| {
+                    sound_system.write_syllable(*stress, &output[bounds[0]..bounds[1]], *tone)
+                })
+                .collect()
+        }
+        _ => syllables
+            .iter()
+            .map(|(syllable, stress, tone)| {
+                let (onset, nucleus, coda) = syllable_parts(syllable);
+                let syllable = match position {
+                    SyllablePosition::Onset => format!(
+                        "{}{}{}",
+                        apply(&letters(onset)),
+                        letters(nucleus),
+                        letters(coda)
+                    ),
+                    SyllablePosition::Coda => format!(
+                        "{}{}{}",
+                        letters(onset),
+                        letters(nucleus),
+                        apply(&letters(coda))
+                    ),
+                    SyllablePosition::Stressed if stress.is_stressed() => apply(&letters(syllable)),
+                    SyllablePosition::Unstressed if !stress.is_stressed() => {
+                        apply(&letters(syllable))
+                    }
+                    _ => letters(syllable),
+                };
+                sound_system.write_syllable(*stress, &syllable, *tone)
+            })
+            .collect(),
+    }
+}
+
+/// Tones are changed from the tones of the input word, so that
+/// `H_H: H -> M` turns `H H H` into `H M H`.
+fn apply_tone_rule(
+    sound_system: &SoundSystem,
+    (before, input, after): (&[String], &'_ str, &[String]),
+    output: &'_ str,
+    word: &'_ str,
+) -> String {
+    let syllables = sound_system.prosodic_syllables(word);
+    let tones = syllables
+        .iter()
+        .map(|(_, _, tone)| tone.map(|toneme| toneme.name.as_str()))
+        .collect::<Vec<_>>();
+    let matches = |index: isize, name: &String| {
+        if name == "#" {
+            index < 0 || index >= tones.len() as isize
+        } else {
+            index >= 0 && tones.get(index as usize).copied().flatten() == Some(name.as_str())
+        }
+    };
+    let changed = (0..tones.len())
+        .filter(|index| {
+            let index = *index as isize;
+            tones[index as usize] == Some(input)
+                && before
+                    .iter()
+                    .rev()
+                    .enumerate()
+                    .all(|(distance, name)| matches(index - 1 - distance as isize, name))
+                && after
+                    .iter()
+                    .enumerate()
+                    .all(|(distance, name)| matches(index + 1 + distance as isize, name))
+        })
         .collect::<Vec<_>>();
+    if changed.is_empty() {
+        return word.to_string();
+    }
+    let toneme = sound_system.tone(output);
+    syllables
+        .iter()
+        .enumerate()
+        .map(|(index, (syllable, stress, tone))| {
+            let tone = if changed.contains(&index) {
+                toneme
+            } else {
+                *tone
+            };
+            sound_system.write_syllable(*stress, &letters(syllable), tone)
+        })
+        .collect()
+}
+
+fn letters(segments: &[Segment]) -> String {
+    segments.iter().map(|(letter, _)| letter.as_str()).collect()
+}
+
+/// Guard against rules that never become stable.
+const MAX_ITERATIONS: usize = 100;
+/// Optional rules double the variants of a word, only the first ones are kept.
+const MAX_VARIANTS: usize = 16;
+
+impl FromStr for RuleApplication {
+    type Err = &'static str;
 
-    let result = rule.replace_all(word, |capture: &Captures| {
-        let mut result = String::new();
-        let first = capture_names.first().map(|t| t.0).unwrap();
-        let last = capture_names.last().map(|t| t.0).unwrap();
-        for i in 1..first {
-            result.push_str(&capture[i]);
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        if s.eq_ignore_ascii_case("optional") {
+            return Ok(RuleApplication::Optional);
         }
+        match s.parse::<f64>() {
+            Ok(probability) if (0.0..=1.0).contains(&probability) => {
+                Ok(RuleApplication::Probability(probability))
+            }
+            Ok(_) => Err("probability not between 0 and 1"),
+            Err(_) => Err("no match"),
+        }
+    }
+}
 
-        match capture_names.len() {
-            1 => {
-                if let Some(replacement_value) = replacement {
-                    result.push_str(&replacement_value)
-                }
+impl FromStr for RuleMode {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "simultaneous" => Ok(RuleMode::Simultaneous),
+            "ltr" | "left-to-right" => Ok(RuleMode::LeftToRight),
+            "rtl" | "right-to-left" => Ok(RuleMode::RightToLeft),
+            "repeat" => Ok(RuleMode::Repeat),
+            _ => Err("no match"),
+        }
+    }
+}
+
+/// Compiled rule and the environment where it does not apply.
+struct Matcher<'r> {
+    pattern: &'r [Pattern],
+    unless: Option<&'r [Pattern]>,
+    letters: Vec<String>,
+}
+
+impl<'r> Matcher<'r> {
+    fn new(
+        sound_system: &SoundSystem,
+        pattern: &'r [Pattern],
+        unless: Option<&'r Vec<Pattern>>,
+    ) -> Self {
+        Matcher {
+            pattern,
+            unless: unless.map(Vec::as_slice),
+            letters: sound_system.segment_letters(),
+        }
+    }
+
+    /// Whether the exception environment matches around the same input.
+    fn is_excepted(&self, segments: &Segments<'_>, found: &Found) -> bool {
+        let input = found.input();
+        self.unless
+            .and_then(|unless| {
+                matcher::find(unless, segments, 0, |other| {
+                    other.range.start <= input.start && other.input() == input
+                })
+            })
+            .is_some()
+    }
+}
+
+/// A match of a rule: the position of its input in the word and what replaces it.
+/// The environment around the input is kept as it is.
+struct RuleMatch {
+    start: usize,
+    end: usize,
+    input: Range<usize>,
+    output: String,
+    /// Whether a syllable boundary follows the input in the rule,
+    /// an insertion then going before the boundary.
+    before_boundary: bool,
+}
+
+fn apply_sound_rule(
+    sound_system: &SoundSystem,
+    mode: RuleMode,
+    rule: &Matcher<'_>,
+    replacement: Option<&String>,
+    word: &'_ str,
+) -> String {
+    apply_sound_rule_to_syllables(sound_system, mode, rule, replacement, (word, vec![])).0
+}
+
+/// Applies a rule to a word with its syllable boundaries, the bytes where
+/// its syllables start, matched by `$`. The boundaries follow the changes
+/// of the letters.
+fn apply_sound_rule_to_syllables(
+    sound_system: &SoundSystem,
+    mode: RuleMode,
+    rule: &Matcher<'_>,
+    replacement: Option<&String>,
+    (word, boundaries): (&'_ str, Vec<usize>),
+) -> (String, Vec<usize>) {
+    let find = |word: &'_ str, boundaries: &[usize], position: usize| {
+        find_match(
+            sound_system,
+            rule,
+            replacement,
+            (word, boundaries),
+            position,
+        )
+    };
+    match mode {
+        RuleMode::Default => {
+            let mut position = 0;
+            let mut found = vec![];
+            while let Some(current) = find(word, &boundaries, position) {
+                position = next_position(word, current.start, current.end);
+                found.push(current);
             }
-            _ => {
-                let to_replace = capture_names
-                    .iter()
-                    .filter(|(_idx, c)| !c.eq_ignore_ascii_case("input"))
-                    .map(|(_idx, class)| {
-                        sound_system
-                            .classes()
-                            .get(&(*class).to_string())
-                            .and_then(|letters| {
-                                let letter = capture.name(class).unwrap().as_str();
-                                letters
-                                    .iter()
-                                    .position(|l| *l == letter)
-                                    .map(|position| (letter, position))
+            splice((word, &boundaries), found)
+        }
+        RuleMode::Simultaneous => splice(
+            (word, &boundaries),
+            all_matches(&find, (word, &boundaries)).into_iter().fold(
+                vec![],
+                |mut found: Vec<RuleMatch>, current| {
+                    if found
+                        .iter()
+                        .all(|previous| !overlaps(&previous.input, &current.input))
+                    {
+                        found.push(current);
+                    }
+                    found
+                },
+            ),
+        ),
+        RuleMode::LeftToRight => {
+            let mut word = word.to_string();
+            let mut boundaries = boundaries;
+            let mut cursor = 0;
+            let mut strict = false;
+            for _ in 0..MAX_ITERATIONS * (word.len() + 1) {
+                let current =
+                    match all_matches(&find, (&word, &boundaries))
+                        .into_iter()
+                        .find(|current| {
+                            current.input.start > cursor
+                                || (!strict && current.input.start == cursor)
+                        }) {
+                        Some(current) => current,
+                        None => return (word, boundaries),
+                    };
+                cursor = current.input.start + current.output.len();
+                strict = current.input.is_empty();
+                let (spliced, moved) = splice((&word, &boundaries), vec![current]);
+                word = spliced;
+                boundaries = moved;
+            }
+            warn!("Left to right application did not finish on {}", word);
+            (word, boundaries)
+        }
+        RuleMode::RightToLeft => {
+            let mut word = word.to_string();
+            let mut boundaries = boundaries;
+            let mut cursor = None;
+            for _ in 0..MAX_ITERATIONS * (word.len() + 1) {
+                let current = match all_matches(&find, (&word, &boundaries))
+                    .into_iter()
+                    .rev()
+                    .find(|current| {
+                        cursor
+                            .map(|cursor| {
+                                current.input.end <= cursor && current.input.start < cursor
                             })
-                            .unwrap()
-                    })
-                    .collect::<Vec<_>>();
-                let mut i = 0;
-                replacement
-                    .map(|value| {
-                        value.chars().for_each(|c| match c {
-                            c if c.is_uppercase() => {
-                                if let Some((original_letter, position)) = to_replace.get(i) {
-                                    let class_name = c.to_string();
-                                    match sound_system
-                                        .classes()
-                                        .get(&class_name as &str)
-                                        .and_then(|letters| letters.get(*position))
-                                    {
-                                        None => {
-                                            trace!("replacement letter not found");
-                                            result.push_str(original_letter)
-                                        }
-
-                                        Some(letter) => {
-                                            trace!("replacement letter: {:#?}", letter);
-                                            result.push_str(letter);
-                                            i += 1;
-                                        }
-                                    }
-                                }
-                            }
-                            _ => result.push(c),
-                        })
-                    })
-                    .unwrap_or_else(|| ());
+                            .unwrap_or(true)
+                    }) {
+                    Some(current) => current,
+                    None => return (word, boundaries),
+                };
+                cursor = Some(current.input.start);
+                let (spliced, moved) = splice((&word, &boundaries), vec![current]);
+                word = spliced;
+                boundaries = moved;
             }
+            warn!("Right to left application did not finish on {}", word);
+            (word, boundaries)
         }
-        for i in last + 1..capture.len() {
-            result.push_str(&capture[i]);
+        RuleMode::Repeat => {
+            let mut word = (word.to_string(), boundaries);
+            for _ in 0..MAX_ITERATIONS {
+                let output = apply_sound_rule_to_syllables(
+                    sound_system,
+                    RuleMode::Default,
+                    rule,
+                    replacement,
+                    (&word.0, word.1.clone()),
+                );
+                if output.0 == word.0 {
+                    return word;
+                }
+                word = output;
+            }
+            warn!(
+                "{} is not stable after {} applications",
+                word.0, MAX_ITERATIONS
+            );
+            word
         }
-        result
+    }
+}
+
+/// Every match of the rule, including the ones sharing their environment.
+fn all_matches<F>(find: &F, (word, boundaries): (&'_ str, &[usize])) -> Vec<RuleMatch>
+where
+    F: Fn(&str, &[usize], usize) -> Option<RuleMatch>,
+{
+    let mut position = 0;
+    let mut result: Vec<RuleMatch> = vec![];
+    while let Some(current) = find(word, boundaries, position) {
+        position = next_position(word, current.start, current.start);
+        if result
+            .last()
+            .map(|previous| previous.input != current.input)
+            .unwrap_or(true)
+        {
+            result.push(current);
+        }
+    }
+    result
+}
+
+fn next_position(word: &'_ str, start: usize, end: usize) -> usize {
+    if end > start {
+        end
+    } else {
+        start
+            + word[start..]
+                .chars()
+                .next()
+                .map(char::len_utf8)
+                .unwrap_or(1)
+    }
+}
+
+fn overlaps(left: &Range<usize>, right: &Range<usize>) -> bool {
+    left.start < right.end && right.start < left.end || left == right
+}
+
+/// Replaces the inputs of the matches, sorted by position, and moves
+/// the syllable boundaries with the letters around them. A boundary inside
+/// a replaced input goes after its output.
+fn splice(
+    (word, boundaries): (&'_ str, &[usize]),
+    matches: Vec<RuleMatch>,
+) -> (String, Vec<usize>) {
+    let mut result = String::new();
+    let mut position = 0;
+    matches.iter().for_each(|current| {
+        result.push_str(&word[position..current.input.start]);
+        result.push_str(&current.output);
+        position = current.input.end;
     });
-    result.to_string()
+    result.push_str(&word[position..]);
+    let boundaries = boundaries
+        .iter()
+        .map(|boundary| {
+            matches.iter().fold(*boundary, |moved, current| {
+                let Range { start, end } = current.input;
+                let output = current.output.len();
+                if end < *boundary || end == *boundary && (start < end || current.before_boundary) {
+                    moved + output - (end - start)
+                } else if start < *boundary && *boundary < end {
+                    moved + output - (*boundary - start)
+                } else {
+                    moved
+                }
+            })
+        })
+        .collect();
+    (result, boundaries)
+}
+
+/// Part of the input or the output of a rule: a class with its index,
+/// as `C1` in `C1 V C2 -> C2 V C1`, or a letter.
+pub(super) enum RulePart<'a> {
+    Class(char, Option<&'a str>),
+    Letter(char),
+}
+
+pub(super) fn rule_parts(pattern: &'_ str) -> Vec<RulePart<'_>> {
+    let mut parts = vec![];
+    let mut chars = pattern.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        if c.is_whitespace() {
+            continue;
+        }
+        if !c.is_uppercase() {
+            parts.push(RulePart::Letter(c));
+            continue;
+        }
+        let from = start + c.len_utf8();
+        let mut to = from;
+        while let Some((position, _)) = chars.next_if(|(_, digit)| digit.is_ascii_digit()) {
+            to = position + 1;
+        }
+        parts.push(RulePart::Class(
+            c,
+            if to > from {
+                Some(&pattern[from..to])
+            } else {
+                None
+            },
+        ));
+    }
+    parts
+}
+
+fn find_match(
+    sound_system: &SoundSystem,
+    rule: &Matcher<'_>,
+    replacement: Option<&String>,
+    (word, boundaries): (&'_ str, &[usize]),
+    start: usize,
+) -> Option<RuleMatch> {
+    if start > word.len() {
+        return None;
+    }
+    let segments = Segments::new(word, &rule.letters).with_boundaries(boundaries);
+    let found = matcher::find(rule.pattern, &segments, segments.index(start), |found| {
+        !rule.is_excepted(&segments, found)
+    })?;
+    let groups = found
+        .captures
+        .iter()
+        .filter(|(name, _)| name != INPUT && name != SYLLABLE)
+        .map(|(name, range)| (name.as_str(), segments.text(range)))
+        .collect::<Vec<_>>();
+    let output = match replacement {
+        Some(replacement) if groups.is_empty() => {
+            replacement.chars().filter(|c| !c.is_whitespace()).collect()
+        }
+        Some(replacement) => replace(sound_system, replacement, &groups),
+        None => String::new(),
+    };
+    let input = found.input();
+    let before_boundary = found
+        .captures
+        .iter()
+        .skip_while(|(name, _)| name != INPUT)
+        .any(|(name, range)| name == SYLLABLE && range.start == input.end);
+    let range = segments.bytes(&found.range);
+    Some(RuleMatch {
+        start: range.start,
+        end: range.end,
+        input: segments.bytes(&input),
+        output,
+        before_boundary,
+    })
+}
+
+/// Letter of `class` at the same position as `letter` in the class `from`.
+fn corresponding_letter<'a>(
+    sound_system: &'a SoundSystem,
+    (from, letter): (char, &'a str),
+    class: char,
+) -> Option<&'a str> {
+    sound_system
+        .classes()
+        .get(&from.to_string())
+        .and_then(|letters| letters.iter().position(|l| l == letter))
+        .and_then(|position| {
+            sound_system
+                .classes()
+                .get(&class.to_string())
+                .and_then(|letters| letters.get(position))
+        })
+        .map(String::as_str)
+}
+
+/// The classes without index take in turn the letters of the input,
+/// the indexed ones the letter captured with the same index.
+fn replace(sound_system: &SoundSystem, replacement: &'_ str, groups: &[(&str, &str)]) -> String {
+    let mut output = String::new();
+    let mut to_replace = groups
+        .iter()
+        .map(|(name, letter)| (group_class(name), *letter))
+        .filter(|((_, index), _)| index.is_none())
+        .map(|((class, _), letter)| (class, letter));
+    rule_parts(replacement)
+        .into_iter()
+        .for_each(|part| match part {
+            RulePart::Letter(c) => output.push(c),
+            RulePart::Class(class, None) => {
+                if let Some(original) = to_replace.next() {
+                    match corresponding_letter(sound_system, original, class) {
+                        None => {
+                            trace!("replacement letter not found");
+                            output.push_str(original.1)
+                        }
+                        Some(letter) => {
+                            trace!("replacement letter: {:#?}", letter);
+                            output.push_str(letter);
+                        }
+                    }
+                }
+            }
+            RulePart::Class(class, Some(index)) => {
+                let original = groups
+                    .iter()
+                    .map(|(name, letter)| (group_class(name), *letter))
+                    .find(|((_, other), _)| *other == Some(index));
+                match original {
+                    Some(((from, _), letter)) if from == class => output.push_str(letter),
+                    Some(((from, _), letter)) => output.push_str(
+                        corresponding_letter(sound_system, (from, letter), class).unwrap_or(letter),
+                    ),
+                    None => warn!("No capture {}{} in the input", class, index),
+                }
+            }
+        });
+    output
 }
 
 #[cfg(test)]
@@ -142,6 +966,10 @@ mod tests {
     use super::*;
     use crate::sound_system::{Rule, SoundSystem};
     use std::collections::HashMap;
+    fn choice(letters: &str) -> Vec<String> {
+        letters.split('|').map(String::from).collect()
+    }
+
     fn create_sound_system(classes: Vec<(String, Vec<String>)>, rules: Vec<Rule>) -> SoundSystem {
         SoundSystem::new(
             classes.iter().cloned().collect(),
@@ -167,10 +995,20 @@ mod tests {
             ],
             vec![Rule::SoundRule {
                 name: "V_*V: S -> Z".to_string(),
-                regex:
-                    "(a|e|o|y|ä|wa|ë|we|ö|wo)(?P<input>(?P<S>p|t|g))(.*?)(a|e|o|y|ä|wa|ë|we|ö|wo)"
-                        .to_string(),
+                pattern: vec![
+                    Pattern::Letters(choice("a|e|o|y|ä|wa|ë|we|ö|wo"), None),
+                    Pattern::Input(vec![Pattern::Letters(
+                        choice("p|t|g"),
+                        Some("S".to_string()),
+                    )]),
+                    Pattern::Gap,
+                    Pattern::Letters(choice("a|e|o|y|ä|wa|ë|we|ö|wo"), None),
+                ],
+                unless: None,
                 replacement: Some("Z".to_string()),
+                mode: RuleMode::Default,
+                application: RuleApplication::Always,
+                exceptions: Default::default(),
             }],
         );
         let words = vec!["apxal".to_string()];
@@ -178,10 +1016,12 @@ mod tests {
         assert_eq!(
             result,
             Transformation {
-                output: vec!["abxal".to_string()],
+                output: vec![vec!["abxal".to_string()]],
                 history: vec![History {
                     rule: "V_*V: S -> Z".to_string(),
-                    words: vec!["abxal".to_string()],
+                    words: vec![vec!["abxal".to_string()]],
+                    stage: None,
+                    blocked: vec![false],
                 }]
             }
         )
@@ -195,10 +1035,19 @@ mod tests {
             )],
             vec![Rule::SoundRule {
                 name: "V_V: S -> x".to_string(),
-                regex:
-                    "(a|e|o|y|ja|wa|je|we|jo|wo)(?P<input>(?P<S>p|t|c))(a|e|o|y|ja|wa|je|we|jo|wo)"
-                        .to_string(),
+                pattern: vec![
+                    Pattern::Letters(choice("a|e|o|y|ja|wa|je|we|jo|wo"), None),
+                    Pattern::Input(vec![Pattern::Letters(
+                        choice("p|t|c"),
+                        Some("S".to_string()),
+                    )]),
+                    Pattern::Letters(choice("a|e|o|y|ja|wa|je|we|jo|wo"), None),
+                ],
+                unless: None,
                 replacement: Some("x".to_string()),
+                mode: RuleMode::Default,
+                application: RuleApplication::Always,
+                exceptions: Default::default(),
             }],
         );
         let words = vec!["apaepal".to_string()];
@@ -206,10 +1055,12 @@ mod tests {
         assert_eq!(
             result,
             Transformation {
-                output: vec!["axaexal".to_string()],
+                output: vec![vec!["axaexal".to_string()]],
                 history: vec![History {
                     rule: "V_V: S -> x".to_string(),
-                    words: vec!["axaexal".to_string()],
+                    words: vec![vec!["axaexal".to_string()]],
+                    stage: None,
+                    blocked: vec![false],
                 }]
             }
         )
@@ -224,10 +1075,19 @@ mod tests {
             )],
             vec![Rule::SoundRule {
                 name: "V_V: S -> Z".to_string(),
-                regex:
-                    "(a|e|o|y|ja|wa|je|we|jo|wo)(?P<input>(?P<S>p|t|c))(a|e|o|y|ja|wa|je|we|jo|wo)"
-                        .to_string(),
+                pattern: vec![
+                    Pattern::Letters(choice("a|e|o|y|ja|wa|je|we|jo|wo"), None),
+                    Pattern::Input(vec![Pattern::Letters(
+                        choice("p|t|c"),
+                        Some("S".to_string()),
+                    )]),
+                    Pattern::Letters(choice("a|e|o|y|ja|wa|je|we|jo|wo"), None),
+                ],
+                unless: None,
                 replacement: Some("Z".to_string()),
+                mode: RuleMode::Default,
+                application: RuleApplication::Always,
+                exceptions: Default::default(),
             }],
         );
         let words = vec!["apal".to_string()];
@@ -235,10 +1095,12 @@ mod tests {
         assert_eq!(
             result,
             Transformation {
-                output: vec!["apal".to_string()],
+                output: vec![vec!["apal".to_string()]],
                 history: vec![History {
                     rule: "V_V: S -> Z".to_string(),
-                    words: vec!["apal".to_string()],
+                    words: vec![vec!["apal".to_string()]],
+                    stage: None,
+                    blocked: vec![false],
                 }]
             }
         )
@@ -247,49 +1109,327 @@ mod tests {
     #[test]
     fn test_sound_transformation() {
         let mut sound_system = create_sound_system(
-        vec![
-            ("S".to_string(), vec!["p".to_string(),"t".to_string(),"c".to_string()]),
-            ("Z".to_string(),vec!["b".to_string(),"d".to_string(),"g".to_string()])
-        ],
-        vec![
-        Rule::SoundRule{
-            name: "#_: l -> ".to_string(),
-            regex: "^(?P<input>l)".to_string(),
-            replacement: None,
-        },
-        Rule::SoundRule{
-            name: "_#: l -> ".to_string(),
-            regex: "(?P<input>l)$".to_string(),
-            replacement: None,
-        },
-        Rule::SoundRule{
-            name: "V_V: S -> Z".to_string(), 
-            regex: "(a|e|o|y|ja|wa|je|we|jo|wo)(?P<input>(?P<S>p|t|c))(a|e|o|y|ja|wa|je|we|jo|wo)".to_string(),
-            replacement: Some("Z".to_string()),
-        }
-        ]);
+            vec![
+                (
+                    "S".to_string(),
+                    vec!["p".to_string(), "t".to_string(), "c".to_string()],
+                ),
+                (
+                    "Z".to_string(),
+                    vec!["b".to_string(), "d".to_string(), "g".to_string()],
+                ),
+            ],
+            vec![
+                Rule::SoundRule {
+                    name: "#_: l -> ".to_string(),
+                    pattern: vec![
+                        Pattern::Start,
+                        Pattern::Input(vec![Pattern::Letter("l".to_string())]),
+                    ],
+                    unless: None,
+                    replacement: None,
+                    mode: RuleMode::Default,
+                    application: RuleApplication::Always,
+                    exceptions: Default::default(),
+                },
+                Rule::SoundRule {
+                    name: "_#: l -> ".to_string(),
+                    pattern: vec![
+                        Pattern::Input(vec![Pattern::Letter("l".to_string())]),
+                        Pattern::End,
+                    ],
+                    unless: None,
+                    replacement: None,
+                    mode: RuleMode::Default,
+                    application: RuleApplication::Always,
+                    exceptions: Default::default(),
+                },
+                Rule::SoundRule {
+                    name: "V_V: S -> Z".to_string(),
+                    pattern: vec![
+                        Pattern::Letters(choice("a|e|o|y|ja|wa|je|we|jo|wo"), None),
+                        Pattern::Input(vec![Pattern::Letters(
+                            choice("p|t|c"),
+                            Some("S".to_string()),
+                        )]),
+                        Pattern::Letters(choice("a|e|o|y|ja|wa|je|we|jo|wo"), None),
+                    ],
+                    unless: None,
+                    replacement: Some("Z".to_string()),
+                    mode: RuleMode::Default,
+                    application: RuleApplication::Always,
+                    exceptions: Default::default(),
+                },
+            ],
+        );
 
         let words = vec!["la".to_string(), "apaacal".to_string()];
         let result = sound_system.sound_trasformation(words);
         assert_eq!(
             result,
             Transformation {
-                output: vec!["a".to_string(), "abaaga".to_string()],
+                output: vec![vec!["a".to_string()], vec!["abaaga".to_string()]],
                 history: vec![
                     History {
                         rule: "#_: l -> ".to_string(),
-                        words: vec!["a".to_string(), "apaacal".to_string()],
+                        words: vec![vec!["a".to_string()], vec!["apaacal".to_string()]],
+                        stage: None,
+                        blocked: vec![false, false],
                     },
                     History {
                         rule: "_#: l -> ".to_string(),
-                        words: vec!["a".to_string(), "apaaca".to_string()],
+                        words: vec![vec!["a".to_string()], vec!["apaaca".to_string()]],
+                        stage: None,
+                        blocked: vec![false, false],
                     },
                     History {
                         rule: "V_V: S -> Z".to_string(),
-                        words: vec!["a".to_string(), "abaaga".to_string()],
+                        words: vec![vec!["a".to_string()], vec!["abaaga".to_string()]],
+                        stage: None,
+                        blocked: vec![false, false],
                     }
                 ]
             }
         )
     }
+
+    #[test]
+    fn test_syllable_rules() {
+        let mut sound_system = crate::sound_system::from_string(
+            "letters: a, k, t, g, d, h\nsyllables: CVC CV VC V\nrules:\n_$: k -> g\ncoda: t -> d\nonset: h -> \n",
+        )
+        .unwrap();
+        let words = vec!["aktak".to_string(), "atka".to_string(), "hatha".to_string()];
+        let result = sound_system.sound_trasformation(words);
+        assert_eq!(
+            result.output,
+            vec![
+                vec!["agtag".to_string()],
+                vec!["adka".to_string()],
+                vec!["ada".to_string()]
+            ]
+        );
+    }
+
+    #[test]
+    fn test_syllable_boundaries() {
+        let transform = |input: &str, word: &str| {
+            crate::sound_system::from_string(input)
+                .unwrap()
+                .sound_trasformation(vec![word.to_string()])
+                .output
+                .remove(0)
+                .remove(0)
+        };
+        let input = "letters: a, e, t, k, .\nphonemes:\n. /ʔ/\nrules:\n$_: t -> k\n";
+        assert_eq!(transform(input, "ta.ta"), "ka.ta");
+        let input = "letters: a, t, h\nsyllables: CV CVC\nrules:\nV_$: -> h\n";
+        assert_eq!(transform(input, "tata"), "tahtah");
+        let input = "letters: a, t, h\nsyllables: CV CVC\nrules:\n[ltr] $_V: -> h\n";
+        assert_eq!(transform(input, "aa"), "haha");
+    }
+
+    #[test]
+    fn test_stages() {
+        let mut sound_system = crate::sound_system::from_string(
+            "letters: a, p, b, f, t\nrules:\n_: t -> d\nstage \"Old\":\n_: p -> b\nstage \"Middle\":\n_: b -> v\n_: a -> e\n",
+        )
+        .unwrap();
+        let words = vec!["pat".to_string()];
+        let result = sound_system.sound_trasformation(words.clone());
+        assert_eq!(result.output, vec![vec!["ved".to_string()]]);
+        assert_eq!(
+            result.stages(),
+            vec![
+                ("Old", &vec![vec!["bad".to_string()]]),
+                ("Middle", &vec![vec!["ved".to_string()]])
+            ]
+        );
+        let result = sound_system
+            .stage_transformation(words.clone(), None, Some("old"))
+            .unwrap();
+        assert_eq!(result.output, vec![vec!["bad".to_string()]]);
+        let result = sound_system
+            .stage_transformation(words.clone(), Some("Middle"), None)
+            .unwrap();
+        assert_eq!(result.output, vec![vec!["pet".to_string()]]);
+        assert!(sound_system
+            .stage_transformation(words, Some("Late"), None)
+            .is_err());
+    }
+
+    fn apply_modes(rule: &str, word: &str) -> Vec<String> {
+        ["", "[simultaneous] ", "[ltr] ", "[rtl] ", "[repeat] "]
+            .iter()
+            .map(|mode| {
+                let input = format!("letters: a, e, p, b\nrules:\n{}{}\n", mode, rule);
+                let mut sound_system = crate::sound_system::from_string(&input).unwrap();
+                sound_system
+                    .sound_trasformation(vec![word.to_string()])
+                    .output
+                    .remove(0)
+                    .remove(0)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_rule_modes() {
+        assert_eq!(
+            apply_modes("V_V: p -> b", "apapa"),
+            vec!["abapa", "ababa", "ababa", "ababa", "ababa"]
+        );
+        assert_eq!(
+            apply_modes("e_: a -> e", "eaa"),
+            vec!["eea", "eea", "eee", "eea", "eee"]
+        );
+        assert_eq!(
+            apply_modes("_e: a -> e", "aae"),
+            vec!["aee", "aee", "aee", "eee", "eee"]
+        );
+        assert_eq!(
+            apply_modes("a_: b -> a", "abbb"),
+            vec!["aabb", "aabb", "aaaa", "aabb", "aaaa"]
+        );
+    }
+
+    #[test]
+    fn test_rule_application() {
+        let mut sound_system = crate::sound_system::from_string(
+            "letters: a, e, p, b, f, v\nrules:\nV_V: p -> b @ 1\nV_V: b -> v @ 0\n_: a -> e @ optional\n_#: e -> @ optional\n",
+        )
+        .unwrap();
+        let result = sound_system.sound_trasformation(vec!["apa".to_string(), "pf".to_string()]);
+        assert_eq!(
+            result.output,
+            vec![
+                vec!["aba".to_string(), "ebe".to_string(), "eb".to_string()],
+                vec!["pf".to_string()]
+            ]
+        );
+        assert_eq!(result.history[2].rule, "_ : a -> e @ optional".to_string());
+    }
+
+    #[test]
+    fn test_rule_probability() {
+        let words = vec![vec!["apa".to_string()]; 1000];
+        let blocked = vec![vec![false]; words.len()];
+        let mut rng = StdRng::seed_from_u64(7);
+        let output = apply_to_variants(
+            &words,
+            (RuleApplication::Probability(0.3), &blocked),
+            &mut rng,
+            |word, _| word.replace('p', "b"),
+        );
+        assert!(output.iter().all(|variants| variants.len() == 1));
+        let changed = output
+            .iter()
+            .filter(|variants| variants[0] == "aba")
+            .count();
+        assert!((250..350).contains(&changed), "{}", changed);
+        assert_eq!(
+            output,
+            apply_to_variants(
+                &words,
+                (RuleApplication::Probability(0.3), &blocked),
+                &mut StdRng::seed_from_u64(7),
+                |word, _| word.replace('p', "b"),
+            )
+        );
+        assert!(
+            crate::sound_system::from_string("letters: a, p, b\nrules:\n_: p -> b @\n").is_err()
+        );
+    }
+
+    #[test]
+    fn test_split_markers() {
+        assert_eq!(
+            split_markers("apa  apa\tapa !lenition"),
+            ("apa  apa\tapa".to_string(), vec!["lenition".to_string()])
+        );
+        assert_eq!(
+            split_markers("!lenition apa !"),
+            ("apa !".to_string(), vec!["lenition".to_string()])
+        );
+    }
+
+    #[test]
+    fn test_exceptions() {
+        let mut sound_system = crate::sound_system::from_string(
+            "letters: a, e, p, b, k\nrules:\nlenition = V_V: p -> b except kapa, papa\n_#: a -> e except /^b/\n",
+        )
+        .unwrap();
+        let words = vec![
+            "apa".to_string(),
+            "kapa".to_string(),
+            "apa !Lenition".to_string(),
+            "bapa".to_string(),
+        ];
+        let result = sound_system.sound_trasformation(words);
+        assert_eq!(
+            result.output,
+            vec![
+                vec!["abe".to_string()],
+                vec!["kape".to_string()],
+                vec!["ape".to_string()],
+                vec!["baba".to_string()]
+            ]
+        );
+        assert_eq!(result.history[0].rule, "lenition = V_V : p -> b");
+        assert_eq!(result.history[0].blocked, vec![false, true, true, false]);
+        assert_eq!(result.history[1].blocked, vec![false, false, false, true]);
+    }
+
+    fn apply_rule(rule: &str, word: &str) -> String {
+        let input = format!(
+            "letters: a, e, i, p, t, k, s\nC = p t k s\nV = a e i\nrules:\n{}\n",
+            rule
+        );
+        let mut sound_system = crate::sound_system::from_string(&input).unwrap();
+        sound_system
+            .sound_trasformation(vec![word.to_string()])
+            .output
+            .remove(0)
+            .remove(0)
+    }
+
+    #[test]
+    fn test_rewrites() {
+        assert_eq!(apply_rule("_: s k -> k s", "aska"), "aksa");
+        assert_eq!(apply_rule("#_: C1 V C2 -> C2 V C1", "pakis"), "kapis");
+        assert_eq!(apply_rule("[simultaneous] C_C: -> e", "aptka"), "apeteka");
+        assert_eq!(apply_rule("C1_: C1 -> ", "appa"), "apa");
+        assert_eq!(apply_rule("C1_: C1 -> ", "apta"), "apta");
+        assert_eq!(apply_rule("V_V: C1 -> C1 C1", "apa"), "appa");
+        assert_eq!(apply_rule("_: C1 C1 -> C1", "aptta"), "apta");
+    }
+
+    #[test]
+    fn test_environments() {
+        assert_eq!(apply_rule("{a,e}_: p -> b", "ipapi"), "ipabi");
+        assert_eq!(apply_rule("#(C)V_: p -> b", "kapa"), "kaba");
+        assert_eq!(apply_rule("#(C)V_: p -> b", "apa"), "aba");
+        assert_eq!(apply_rule("#(C)V_: p -> b", "ikapa"), "ikapa");
+        assert_eq!(apply_rule("!V_: s -> ", "ksa"), "ka");
+        assert_eq!(apply_rule("[^V]_: s -> ", "asa"), "asa");
+        assert_eq!(apply_rule("#C+_: a -> e", "ptka"), "ptke");
+        assert_eq!(apply_rule("#C+_: a -> e", "apa"), "apa");
+        assert_eq!(apply_rule("a*_*a: p -> b", "akpka"), "akbka");
+        assert_eq!(apply_rule("a*_*a: p -> b", "kpka"), "kpka");
+        assert_eq!(apply_rule("_ // #_: p -> b", "papa"), "paba");
+    }
+
+    #[test]
+    fn test_stress_rules() {
+        let mut sound_system = crate::sound_system::from_string(
+            "letters: a, e, k, t\nsyllables: CV\nstress: penultimate\nrules:\nunstressed: a -> e\nstressed: e -> a\n",
+        )
+        .unwrap();
+        let words = vec!["katata".to_string(), "teka".to_string()];
+        let result = sound_system.sound_trasformation(words);
+        assert_eq!(
+            result.output,
+            vec![vec!["ketate".to_string()], vec!["take".to_string()]]
+        );
+    }
 }
@@ -0,0 +1,121 @@
+use super::phone::alignment_distance;
+use super::{MonoSyllableRepartition, SoundSystem};
+use std::cmp::Ordering;
+
+/// Generation gives up after this many words generated per word asked.
+const MAX_ATTEMPTS: usize = 20;
+
+impl SoundSystem {
+    /// Alignment distance between the phones of two words,
+    /// 1 being about one sound added, removed or replaced.
+    pub fn word_distance(&self, left: &'_ str, right: &'_ str) -> f64 {
+        alignment_distance(&self.phones(left), &self.phones(right))
+    }
+
+    /// Words of `words` at most `max_distance` from `word`, the nearest first.
+    pub fn sounds_like(
+        &self,
+        word: &'_ str,
+        words: &[String],
+        max_distance: f64,
+    ) -> Vec<(String, f64)> {
+        let phones = self.phones(word);
+        let mut result = words
+            .iter()
+            .map(|other| {
+                (
+                    other.clone(),
+                    alignment_distance(&phones, &self.phones(other)),
+                )
+            })
+            .filter(|(_, distance)| *distance <= max_distance)
+            .collect::<Vec<_>>();
+        result.sort_by(|(left, left_distance), (right, right_distance)| {
+            left_distance
+                .partial_cmp(right_distance)
+                .unwrap_or(Ordering::Equal)
+                .then_with(|| left.cmp(right))
+        });
+        result
+    }
+
+    /// Generates words at least `min_distance` from the words of `lexicon`
+    /// and from each other, so that they are not near-homophones.
+    /// Fewer words are returned when too many attempts are rejected.
+    pub fn generate_distinct_words(
+        &self,
+        number: usize,
+        repartition: MonoSyllableRepartition,
+        lexicon: &[String],
+        min_distance: f64,
+    ) -> Vec<String> {
+        let mut existing = lexicon
+            .iter()
+            .map(|word| self.phones(word))
+            .collect::<Vec<_>>();
+        let mut words: Vec<String> = vec![];
+        let mut attempts = 0;
+        while words.len() < number && attempts < number * MAX_ATTEMPTS {
+            let missing = number - words.len();
+            attempts += missing;
+            for word in self.generate_words(missing, repartition) {
+                let phones = self.phones(&word);
+                if !words.contains(&word)
+                    && existing
+                        .iter()
+                        .all(|other| alignment_distance(&phones, other) >= min_distance)
+                {
+                    existing.push(phones);
+                    words.push(word);
+                }
+            }
+        }
+        if words.len() < number {
+            warn!(
+                "Only {} words are at least {} from the others",
+                words.len(),
+                min_distance
+            );
+        }
+        words
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sound_system() -> SoundSystem {
+        crate::sound_system::from_string("letters: a, i, u, p, t, k, s, m\nsyllables: CV CVC\n")
+            .unwrap()
+    }
+
+    #[test]
+    fn test_sounds_like() {
+        let words = ["kata", "kasa", "kama", "pita", "katu"]
+            .iter()
+            .map(|word| word.to_string())
+            .collect::<Vec<_>>();
+        let found = sound_system()
+            .sounds_like("kata", &words, 0.5)
+            .into_iter()
+            .map(|(word, _)| word)
+            .collect::<Vec<_>>();
+        assert_eq!(found, vec!["kata", "kasa"]);
+    }
+
+    #[test]
+    fn test_distinct_words() {
+        let sound_system = sound_system();
+        let lexicon = vec!["kata".to_string(), "pimu".to_string()];
+        let words =
+            sound_system.generate_distinct_words(10, MonoSyllableRepartition::Never, &lexicon, 2.0);
+        assert!(!words.is_empty());
+        words.iter().enumerate().for_each(|(index, word)| {
+            lexicon
+                .iter()
+                .chain(words[index + 1..].iter())
+                .for_each(|other| assert!(sound_system.word_distance(word, other) >= 2.0));
+        });
+    }
+}
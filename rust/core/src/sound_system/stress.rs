@@ -0,0 +1,204 @@
+use super::phone::{Phone, PhoneProperty, Rhythm};
+use super::syllable::{syllable_parts, syllable_starts};
+use super::{Segment, SoundSystem, Stress, StressPlacement, SyllableStress};
+use std::str::FromStr;
+
+const PRIMARY_MARKS: [char; 2] = ['ˈ', '\''];
+const SECONDARY_MARK: char = 'ˌ';
+
+impl FromStr for StressPlacement {
+    type Err = &'static str;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "initial" => Ok(StressPlacement::Initial),
+            "final" => Ok(StressPlacement::Final),
+            "penultimate" => Ok(StressPlacement::Penultimate),
+            "antepenultimate" => Ok(StressPlacement::Antepenultimate),
+            "weight" => Ok(StressPlacement::Weight),
+            "lexical" => Ok(StressPlacement::Lexical),
+            _ => Err("no match"),
+        }
+    }
+}
+
+impl SyllableStress {
+    pub fn phone(self) -> Option<Phone> {
+        match self {
+            SyllableStress::Primary => Some(Phone::Diacritic(PhoneProperty::Rhythm(
+                Rhythm::PrimaryStress,
+            ))),
+            SyllableStress::Secondary => Some(Phone::Diacritic(PhoneProperty::Rhythm(
+                Rhythm::SecondaryStress,
+            ))),
+            SyllableStress::Unstressed => None,
+        }
+    }
+
+    pub fn is_stressed(self) -> bool {
+        self != SyllableStress::Unstressed
+    }
+}
+
+impl SoundSystem {
+    /// Stress of each syllable of `word`.
+    pub fn stresses(&self, word: &'_ str) -> Vec<SyllableStress> {
+        self.stressed_syllables(word)
+            .into_iter()
+            .map(|(_, stress)| stress)
+            .collect()
+    }
+
+    pub(super) fn stressed_syllables(&self, word: &'_ str) -> Vec<(Vec<Segment>, SyllableStress)> {
+        let (_, marks) = self.stress_marks(&self.tone_marks(word).0);
+        let syllables = self.syllabify_segments(word);
+        let stresses = match self.stress().placement {
+            StressPlacement::None => vec![SyllableStress::Unstressed; syllables.len()],
+            StressPlacement::Lexical => lexical_stresses(&syllables, &marks),
+            placement => fixed_stresses(
+                *self.stress(),
+                primary(placement, &syllables),
+                syllables.len(),
+            ),
+        };
+        syllables.into_iter().zip(stresses).collect()
+    }
+
+    /// Removes the lexical stress marks of `word`, returning them
+    /// with their position in the word without marks.
+    pub(super) fn stress_marks(&self, word: &'_ str) -> (String, Vec<(usize, SyllableStress)>) {
+        let mut result = String::new();
+        let mut marks = vec![];
+        if self.stress().placement != StressPlacement::Lexical {
+            return (word.to_string(), marks);
+        }
+        for c in word.chars() {
+            let position = result.chars().count();
+            if c == SECONDARY_MARK {
+                marks.push((position, SyllableStress::Secondary))
+            } else if PRIMARY_MARKS.contains(&c)
+                && !self
+                    .phonemes_sorted()
+                    .iter()
+                    .any(|(letter, _)| letter.contains(c))
+            {
+                marks.push((position, SyllableStress::Primary))
+            } else {
+                result.push(c)
+            }
+        }
+        (result, marks)
+    }
+
+    /// Mark kept in front of a syllable when rules rewrite a word.
+    pub(super) fn lexical_mark(&self, stress: SyllableStress) -> &'static str {
+        match (self.stress().placement, stress) {
+            (StressPlacement::Lexical, SyllableStress::Primary) => "ˈ",
+            (StressPlacement::Lexical, SyllableStress::Secondary) => "ˌ",
+            _ => "",
+        }
+    }
+}
+
+fn primary(placement: StressPlacement, syllables: &[Vec<Segment>]) -> usize {
+    let size = syllables.len();
+    match placement {
+        StressPlacement::Final => size.saturating_sub(1),
+        StressPlacement::Penultimate => size.saturating_sub(2),
+        StressPlacement::Antepenultimate => size.saturating_sub(3),
+        StressPlacement::Weight if size < 3 || is_heavy(&syllables[size - 2]) => {
+            size.saturating_sub(2)
+        }
+        StressPlacement::Weight => size - 3,
+        _ => 0,
+    }
+}
+
+fn fixed_stresses(stress: Stress, primary: usize, size: usize) -> Vec<SyllableStress> {
+    (0..size)
+        .map(|index| {
+            if index == primary {
+                SyllableStress::Primary
+            } else if stress.secondary && (index as isize - primary as isize) % 2 == 0 {
+                SyllableStress::Secondary
+            } else {
+                SyllableStress::Unstressed
+            }
+        })
+        .collect()
+}
+
+fn lexical_stresses(
+    syllables: &[Vec<Segment>],
+    marks: &[(usize, SyllableStress)],
+) -> Vec<SyllableStress> {
+    let starts = syllable_starts(syllables);
+    let mut stresses = vec![SyllableStress::Unstressed; syllables.len()];
+    marks.iter().for_each(|(position, stress)| {
+        if let Some(index) = starts.iter().rposition(|start| start <= position) {
+            stresses[index] = *stress;
+        }
+    });
+    stresses
+}
+
+/// A syllable is heavy with a coda or a long nucleus.
+fn is_heavy(syllable: &[Segment]) -> bool {
+    let (_, nucleus, coda) = syllable_parts(syllable);
+    let vowels = nucleus
+        .iter()
+        .flat_map(|(_, phones)| phones)
+        .filter(|phone| matches!(phone, Phone::Vowel(_)))
+        .count();
+    let long = nucleus
+        .iter()
+        .flat_map(|(_, phones)| phones)
+        .any(|phone| matches!(phone, Phone::Diacritic(PhoneProperty::Rhythm(Rhythm::Long))));
+    !coda.is_empty() || vowels > 1 || long
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn create_sound_system(stress: &str) -> SoundSystem {
+        let input = format!(
+            "letters: a, e, i, o, k, t, n, r, s\nsyllables: CV CVC V\nstress: {}\n",
+            stress
+        );
+        crate::sound_system::from_string(&input).unwrap()
+    }
+
+    #[test]
+    fn test_fixed_stress() {
+        let sound_system = create_sound_system("penultimate");
+        assert_eq!(sound_system.ipa_representation("katena"), "kaˈte.na");
+        assert_eq!(sound_system.ipa_representation("ka"), "ka");
+        let sound_system = create_sound_system("initial, alternating");
+        assert_eq!(sound_system.ipa_representation("katenari"), "ˈka.teˌna.ri");
+    }
+
+    #[test]
+    fn test_weight_stress() {
+        let sound_system = create_sound_system("weight");
+        assert_eq!(sound_system.ipa_representation("katena"), "ˈka.te.na");
+        assert_eq!(sound_system.ipa_representation("katensa"), "kaˈten.sa");
+        assert_eq!(sound_system.ipa_representation("kata"), "ˈka.ta");
+    }
+
+    #[test]
+    fn test_lexical_stress() {
+        let sound_system = create_sound_system("lexical");
+        assert_eq!(sound_system.ipa_representation("ka'tena"), "kaˈte.na");
+        assert_eq!(sound_system.ipa_representation("ˌkateˈna"), "ˌka.teˈna");
+        assert_eq!(sound_system.syllabify("ka'tena"), vec!["ka", "te", "na"]);
+        assert_eq!(
+            sound_system.stresses("ka'tena"),
+            vec![
+                SyllableStress::Unstressed,
+                SyllableStress::Primary,
+                SyllableStress::Unstressed
+            ]
+        );
+    }
+}
@@ -0,0 +1,205 @@
+use super::phone::Phone;
+use super::{Segment, SoundSystem};
+use std::collections::HashMap;
+
+impl SoundSystem {
+    /// Splits a word into syllables.
+    ///
+    /// The `syllables:` patterns are tried first, preferring the earliest
+    /// boundaries so that onsets are maximal. When the word does not match
+    /// the patterns, every vowel is a nucleus with the maximal onset.
+    pub fn syllabify(&self, word: &'_ str) -> Vec<String> {
+        self.syllabify_segments(word)
+            .iter()
+            .map(|syllable| syllable.iter().map(|(letter, _)| letter.as_str()).collect())
+            .collect()
+    }
+
+    pub fn syllabify_segments(&self, word: &'_ str) -> Vec<Vec<Segment>> {
+        let segments = self.segments(&self.tone_marks(&self.stress_marks(word).0).0);
+        let ends = self
+            .pattern_boundaries(&segments)
+            .unwrap_or_else(|| maximal_onset(&segments));
+        let mut start = 0;
+        ends.into_iter()
+            .map(|end| {
+                let syllable = segments[start..end].to_vec();
+                start = end;
+                syllable
+            })
+            .collect()
+    }
+
+    pub(super) fn pattern_boundaries(&self, segments: &[Segment]) -> Option<Vec<usize>> {
+        if self.syllables().is_empty() || segments.is_empty() {
+            return None;
+        }
+        let mut cache = HashMap::new();
+        let mut ends = vec![];
+        let mut start = 0;
+        while start < segments.len() {
+            let end = (start + 1..=segments.len()).find(|end| {
+                self.is_syllable(&segments[start..*end]) && self.parses(segments, *end, &mut cache)
+            })?;
+            ends.push(end);
+            start = end;
+        }
+        Some(ends)
+    }
+
+    fn parses(&self, segments: &[Segment], start: usize, cache: &mut HashMap<usize, bool>) -> bool {
+        if start == segments.len() {
+            return true;
+        }
+        if let Some(result) = cache.get(&start) {
+            return *result;
+        }
+        let result = (start + 1..=segments.len()).any(|end| {
+            self.is_syllable(&segments[start..end]) && self.parses(segments, end, cache)
+        });
+        cache.insert(start, result);
+        result
+    }
+
+    pub(super) fn is_syllable(&self, segments: &[Segment]) -> bool {
+        self.syllables()
+            .iter()
+            .any(|pattern| self.pattern_matches(pattern, segments))
+    }
+
+    fn pattern_matches(&self, pattern: &[String], segments: &[Segment]) -> bool {
+        match pattern.first() {
+            None => segments.is_empty(),
+            Some(name) => {
+                let class_name = name.trim_end_matches('?');
+                (name.ends_with('?') && self.pattern_matches(&pattern[1..], segments))
+                    || segments
+                        .first()
+                        .map(|segment| {
+                            self.segment_in_class(segment, class_name)
+                                && self.pattern_matches(&pattern[1..], &segments[1..])
+                        })
+                        .unwrap_or(false)
+            }
+        }
+    }
+
+    pub(super) fn segment_in_class(&self, (letter, phones): &Segment, class_name: &'_ str) -> bool {
+        self.classes()
+            .get(class_name)
+            .map(|letters| letters.contains(letter))
+            .unwrap_or(false)
+            || phones
+                .first()
+                .and_then(|phone| phone.classes())
+                .map(|classes| classes.contains(&class_name))
+                .unwrap_or(false)
+    }
+}
+
+/// Splits a syllable into its onset, nucleus and coda.
+pub fn syllable_parts(syllable: &[Segment]) -> (&[Segment], &[Segment], &[Segment]) {
+    match syllable.iter().position(is_vowel) {
+        None => (syllable, &[], &[]),
+        Some(first) => {
+            let last = syllable[first..]
+                .iter()
+                .position(|segment| !is_vowel(segment))
+                .map(|size| first + size)
+                .unwrap_or_else(|| syllable.len());
+            (
+                &syllable[..first],
+                &syllable[first..last],
+                &syllable[last..],
+            )
+        }
+    }
+}
+
+/// Position of the first letter of each syllable.
+pub(super) fn syllable_starts(syllables: &[Vec<Segment>]) -> Vec<usize> {
+    let mut start = 0;
+    syllables
+        .iter()
+        .map(|syllable| {
+            let current = start;
+            start += syllable
+                .iter()
+                .map(|(letter, _)| letter.chars().count())
+                .sum::<usize>();
+            current
+        })
+        .collect()
+}
+
+fn is_vowel((_, phones): &Segment) -> bool {
+    phones.iter().any(|phone| matches!(phone, Phone::Vowel(_)))
+}
+
+fn maximal_onset(segments: &[Segment]) -> Vec<usize> {
+    let nuclei = segments
+        .iter()
+        .enumerate()
+        .filter(|(_, segment)| is_vowel(segment))
+        .map(|(i, _)| i)
+        .collect::<Vec<_>>();
+    let mut ends = nuclei
+        .windows(2)
+        .map(|pair| {
+            let (previous, next) = (pair[0], pair[1]);
+            if next == previous + 1 {
+                next
+            } else {
+                previous + 1
+            }
+        })
+        .collect::<Vec<_>>();
+    ends.push(segments.len());
+    ends
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sound_system(syllables: &str) -> SoundSystem {
+        let input = format!(
+            "letters: a, e, i, o, k, t, p, s, r, th\nsyllables: {}\n",
+            syllables
+        );
+        crate::sound_system::from_string(&input).unwrap()
+    }
+
+    #[test]
+    fn test_maximal_onset() {
+        let sound_system = sound_system("V CV CVC VC CCV");
+        assert_eq!(sound_system.syllabify("aka"), vec!["a", "ka"]);
+        assert_eq!(sound_system.syllabify("akta"), vec!["a", "kta"]);
+        assert_eq!(sound_system.syllabify("astra"), vec!["as", "tra"]);
+        assert_eq!(sound_system.syllabify("aspra"), vec!["as", "pra"]);
+    }
+
+    #[test]
+    fn test_optional_and_digraphs() {
+        let sound_system = sound_system("C?VC?");
+        assert_eq!(sound_system.syllabify("thetho"), vec!["the", "tho"]);
+        assert_eq!(sound_system.syllabify("oatak"), vec!["o", "a", "tak"]);
+    }
+
+    #[test]
+    fn test_fallback_without_pattern() {
+        let sound_system = sound_system("CV");
+        assert_eq!(sound_system.syllabify("astrak"), vec!["a", "strak"]);
+        assert_eq!(sound_system.ipa_representation("astrak"), "a.strak");
+    }
+
+    #[test]
+    fn test_syllable_parts() {
+        let sound_system = sound_system("CVC");
+        let segments = sound_system.segments("stoik");
+        let (onset, nucleus, coda) = syllable_parts(&segments);
+        assert_eq!(onset.len(), 2);
+        assert_eq!(nucleus.len(), 2);
+        assert_eq!(coda.len(), 1);
+    }
+}
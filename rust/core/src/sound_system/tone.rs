@@ -0,0 +1,145 @@
+use super::distribution;
+use super::syllable::syllable_starts;
+use super::{Segment, SoundSystem, SyllableStress, Toneme};
+use std::cmp::Reverse;
+
+/// Syllable with its stress and tone.
+pub type ProsodicSyllable<'a> = (Vec<Segment>, SyllableStress, Option<&'a Toneme>);
+
+impl SoundSystem {
+    pub fn tone(&self, name: &'_ str) -> Option<&Toneme> {
+        self.tones().iter().find(|toneme| toneme.name == name)
+    }
+
+    /// Tone of each syllable of `word`.
+    pub fn syllable_tones(&self, word: &'_ str) -> Vec<Option<&Toneme>> {
+        let (_, marks) = self.tone_marks(&self.stress_marks(word).0);
+        let starts = syllable_starts(&self.syllabify_segments(word));
+        let mut tones = vec![None; starts.len()];
+        marks.into_iter().for_each(|(position, toneme)| {
+            if let Some(index) = starts.iter().rposition(|start| *start < position) {
+                tones[index] = Some(toneme);
+            }
+        });
+        tones
+    }
+
+    pub(super) fn prosodic_syllables(&self, word: &'_ str) -> Vec<ProsodicSyllable<'_>> {
+        self.stressed_syllables(word)
+            .into_iter()
+            .zip(self.syllable_tones(word))
+            .map(|((syllable, stress), tone)| (syllable, stress, tone))
+            .collect()
+    }
+
+    /// Removes the tone marks written after the syllables of `word`,
+    /// returning them with their position in the word without marks.
+    pub(super) fn tone_marks(&self, word: &'_ str) -> (String, Vec<(usize, &Toneme)>) {
+        let mut tonemes = self
+            .tones()
+            .iter()
+            .filter(|toneme| {
+                !self
+                    .phonemes_sorted()
+                    .iter()
+                    .any(|(letter, _)| *letter == toneme.mark)
+            })
+            .collect::<Vec<_>>();
+        tonemes.sort_by_key(|toneme| Reverse(toneme.mark.len()));
+        let mut result = String::new();
+        let mut marks = vec![];
+        let mut rest = word;
+        while let Some(c) = rest.chars().next() {
+            match tonemes
+                .iter()
+                .find(|toneme| !toneme.mark.is_empty() && rest.starts_with(&toneme.mark))
+            {
+                Some(toneme) => {
+                    marks.push((result.chars().count(), *toneme));
+                    rest = &rest[toneme.mark.len()..];
+                }
+                None => {
+                    result.push(c);
+                    rest = &rest[c.len_utf8()..];
+                }
+            }
+        }
+        (result, marks)
+    }
+
+    /// Writes a syllable back with its lexical stress and tone marks.
+    pub(super) fn write_syllable(
+        &self,
+        stress: SyllableStress,
+        letters: &'_ str,
+        tone: Option<&Toneme>,
+    ) -> String {
+        format!(
+            "{}{}{}",
+            self.lexical_mark(stress),
+            letters,
+            tone.map(|toneme| toneme.mark.as_str()).unwrap_or("")
+        )
+    }
+
+    pub(super) fn random_tone(&self) -> Option<&Toneme> {
+        if self.tones().is_empty() {
+            return None;
+        }
+        let weights = self
+            .tones()
+            .iter()
+            .map(|toneme| (toneme.name.as_str(), toneme.weight))
+            .collect::<Vec<_>>();
+        let name = distribution::select(&distribution::frequency(&weights)).to_string();
+        self.tone(&name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    const INPUT: &str = "letters: a, i, k, m, n
+syllables: CV CVC
+tones:
+  H /˥/ 1 : 3
+  M /˧/ 2
+  L /˩/ 3 : 1
+rules:
+  tone H_H: H -> M
+  tone #_L: L -> H
+";
+
+    #[test]
+    fn test_tone_marks() {
+        let sound_system = crate::sound_system::from_string(INPUT).unwrap();
+        assert_eq!(sound_system.syllabify("ka1nin3"), vec!["ka", "nin"]);
+        assert_eq!(
+            sound_system
+                .syllable_tones("ka1nin3")
+                .iter()
+                .map(|toneme| toneme.map(|t| t.name.as_str()))
+                .collect::<Vec<_>>(),
+            vec![Some("H"), Some("L")]
+        );
+        assert_eq!(sound_system.ipa_representation("ka1nin3"), "ka˥.ni˩n");
+    }
+
+    #[test]
+    fn test_tone_sandhi() {
+        let mut sound_system = crate::sound_system::from_string(INPUT).unwrap();
+        let words = vec![
+            "ka1ka1ka1".to_string(),
+            "ma3na3".to_string(),
+            "ma2na3".to_string(),
+        ];
+        let result = sound_system.sound_trasformation(words);
+        assert_eq!(
+            result.output,
+            vec![
+                vec!["ka1ka2ka1".to_string()],
+                vec!["ma1na3".to_string()],
+                vec!["ma2na3".to_string()]
+            ]
+        );
+    }
+}
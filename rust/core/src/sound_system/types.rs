@@ -3,13 +3,15 @@ use crate::wgl;
 use std::cmp::Ordering;
 use std::collections::HashMap;
 use std::convert::Into;
+use std::ops::Range;
 pub type Syllable = Vec<String>;
 pub type Distribution = (String, f64);
 pub type PhonemeCondition = (phone::Phones, Condition);
 pub type Phoneme = (String, Vec<PhonemeCondition>);
+pub type Segment = (String, phone::Phones);
 
 #[repr(u8)]
-#[derive(Debug)]
+#[derive(Debug, Clone, Copy)]
 pub enum MonoSyllableRepartition {
     Always,
     Mostly,
@@ -19,7 +21,7 @@ pub enum MonoSyllableRepartition {
     Never,
 }
 
-#[derive(Default, Debug, Serialize)]
+#[derive(Default, Debug, Serialize, Clone)]
 pub struct SoundSystem {
     classes: HashMap<String, Vec<String>>,
     phonemes: HashMap<String, Vec<PhonemeCondition>>,
@@ -27,19 +29,319 @@ pub struct SoundSystem {
     syllables: Vec<Syllable>,
     distribution: Vec<Distribution>,
     rules: Vec<Rule>,
+    inventory: phone::Inventory,
+    orthographies: Vec<Orthography>,
+    stress: Stress,
+    tones: Vec<Toneme>,
+    harmonies: Vec<Harmony>,
+    stages: Vec<Stage>,
+    affixes: Vec<Affix>,
+    paradigms: Vec<Paradigm>,
+    roots: Roots,
+    names: Vec<NameProfile>,
+}
+
+/// Named group of consecutive rules.
+#[derive(Debug, Serialize, Clone)]
+pub struct Stage {
+    pub name: String,
+    pub rules: Range<usize>,
+}
+
+/// Affix named by its gloss, the first allomorph whose condition holds being used.
+#[derive(Debug, Serialize, Clone)]
+pub struct Affix {
+    pub gloss: String,
+    pub allomorphs: Vec<Allomorph>,
+}
+
+/// Letters added before the stem, before its first vowel and after it.
+/// An allomorph with both a prefix and a suffix is a circumfix.
+#[derive(Debug, Serialize, Clone, Default, PartialEq)]
+pub struct Allomorph {
+    pub prefix: String,
+    pub infix: String,
+    pub suffix: String,
+    pub reduplication: Option<Reduplication>,
+    pub condition: AffixCondition,
+}
+
+/// Copy of a part of the word added before it, or after it
+/// when the part is taken from the end of the word.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Reduplication {
+    pub reduplicant: Reduplicant,
+    pub prefixed: bool,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub enum Reduplicant {
+    Word,
+    Syllable,
+    /// Letters of these classes, a fixed letter overwriting the copied one as in `Ce`.
+    Shape(Vec<ShapePart>),
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub enum ShapePart {
+    Class(String),
+    Fixed(String),
+}
+
+/// Letters next to where an allomorph goes.
+#[derive(Debug, Default, Serialize, Clone, PartialEq)]
+pub enum AffixCondition {
+    #[default]
+    Always,
+    /// The letter before the allomorph is one of these.
+    After(Vec<String>),
+    /// The letter after the allomorph is one of these.
+    Before(Vec<String>),
+}
+
+/// Dimensions such as case and number, each cell of the paradigm
+/// taking one gloss of every dimension.
+#[derive(Debug, Serialize, Clone)]
+pub struct Paradigm {
+    pub name: String,
+    pub dimensions: Vec<Dimension>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Dimension {
+    pub name: String,
+    pub glosses: Vec<String>,
+}
+
+/// Consonantal roots interdigitated with templates.
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct Roots {
+    /// Classes of the consonants of each kind of root, such as `CCC`.
+    pub patterns: Vec<Syllable>,
+    pub exceptions: Vec<RootException>,
+    pub templates: Vec<Template>,
+}
+
+/// Kind of names, such as the names of rivers, generated with their own
+/// syllables and length and fixed affixes.
+#[derive(Debug, Default, Serialize, Clone)]
+pub struct NameProfile {
+    pub name: String,
+    /// Syllable patterns, those of the language when empty.
+    pub syllables: Vec<Syllable>,
+    /// Numbers of syllables with their weight, the length of the words when empty.
+    pub lengths: Vec<(usize, f64)>,
+    pub prefixes: Vec<String>,
+    pub suffixes: Vec<String>,
+    pub capitalize: bool,
+}
+
+/// Roots that are never generated.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub enum RootException {
+    /// The consonants at these positions, from 0, are the same.
+    Identical(usize, usize),
+    /// A letter of the first class followed by a letter of the second one.
+    Adjacent(String, String),
+}
+
+/// Word pattern such as `C1aC2iC3` giving a word of each root.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub struct Template {
+    pub name: String,
+    pub parts: Vec<TemplatePart>,
+}
+
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub enum TemplatePart {
+    Letters(String),
+    /// Consonant of the root at this position, from 0.
+    Radical(usize),
+}
+
+/// Vowels of a generated word agree on `kind`, except the neutral letters.
+#[derive(Debug, Serialize, Clone)]
+pub struct Harmony {
+    pub kind: HarmonyKind,
+    pub neutral: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Eq, PartialEq, Clone)]
+pub enum HarmonyKind {
+    Classes(Vec<String>),
+    Backness,
+    Roundness,
+    Height,
+}
+
+/// Tone of a syllable from `tones:`, written after the syllable with its mark.
+#[derive(Debug, Serialize, Clone)]
+pub struct Toneme {
+    pub name: String,
+    pub phones: phone::Phones,
+    pub mark: String,
+    pub weight: f64,
+}
+
+/// Placement of stress from `stress:`.
+#[derive(Debug, Default, Serialize, Eq, PartialEq, Clone, Copy)]
+pub struct Stress {
+    pub placement: StressPlacement,
+    /// Secondary stress on every other syllable from the primary one.
+    pub secondary: bool,
+}
+
+#[derive(Debug, Default, Serialize, Eq, PartialEq, Clone, Copy)]
+pub enum StressPlacement {
+    #[default]
+    None,
+    Initial,
+    Final,
+    Penultimate,
+    Antepenultimate,
+    /// Penultimate when it is heavy, antepenultimate otherwise.
+    Weight,
+    /// Marked in the words with `ˈ` or `'` and `ˌ`.
+    Lexical,
+}
+
+#[derive(Debug, Serialize, Eq, PartialEq, Clone, Copy)]
+pub enum SyllableStress {
+    Primary,
+    Secondary,
+    Unstressed,
+}
+
+/// Spelling of words from their phones.
+#[derive(Debug, Serialize, Clone)]
+pub struct Orthography {
+    pub name: String,
+    pub graphemes: Vec<Grapheme>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct Grapheme {
+    pub phones: phone::Phones,
+    pub letters: String,
+    pub condition: Condition,
 }
 
 #[derive(Debug, Serialize, Clone)]
 pub enum Rule {
     SoundRule {
         name: String,
-        regex: String,
+        pattern: Vec<Pattern>,
+        /// Environment where the rule does not apply.
+        unless: Option<Vec<Pattern>>,
         replacement: Option<String>,
+        mode: RuleMode,
+        application: RuleApplication,
+        exceptions: Exceptions,
     },
     PhonemeRule {
         name: String,
         phoneme_differences: Vec<PhonemeDifference>,
     },
+    SyllableRule {
+        name: String,
+        position: SyllablePosition,
+        pattern: Vec<Pattern>,
+        /// Environment where the rule does not apply.
+        unless: Option<Vec<Pattern>>,
+        replacement: Option<String>,
+        mode: RuleMode,
+        application: RuleApplication,
+        exceptions: Exceptions,
+    },
+    /// Copies a part of the word, see `Reduplication`.
+    ReduplicationRule {
+        name: String,
+        reduplication: Reduplication,
+        application: RuleApplication,
+        exceptions: Exceptions,
+    },
+    /// Tone sandhi, each tone of the environment is a syllable.
+    ToneRule {
+        name: String,
+        before: Vec<String>,
+        input: String,
+        after: Vec<String>,
+        output: String,
+        application: RuleApplication,
+        exceptions: Exceptions,
+    },
+}
+
+/// How the matches of a rule are replaced.
+#[derive(Debug, Default, Serialize, Eq, PartialEq, Clone, Copy)]
+pub enum RuleMode {
+    /// One pass over the word, the matches do not share their environment.
+    #[default]
+    Default,
+    /// Every match of the input word is replaced at once.
+    Simultaneous,
+    /// Each replacement is visible to the following matches.
+    LeftToRight,
+    RightToLeft,
+    /// Until the word does not change anymore.
+    Repeat,
+}
+
+/// Environment and input of a rule, matched on the letters of a word.
+#[derive(Debug, Serialize, Clone, PartialEq)]
+pub enum Pattern {
+    /// Beginning of the word, in a phrase also after a word boundary.
+    Start,
+    End,
+    /// Syllable boundary, the beginning or the end of the word.
+    SyllableBoundary,
+    /// Boundary between the elements of a compound.
+    MorphemeBoundary,
+    /// Boundary between two words of a phrase.
+    WordBoundary,
+    Letter(String),
+    /// One of the letters, captured with the name when there is one.
+    Letters(Vec<String>, Option<String>),
+    Alternation(Vec<Vec<Pattern>>),
+    Optional(Vec<Pattern>),
+    /// One or more times.
+    Repeat(Vec<Pattern>),
+    /// Any letters, as few as possible.
+    Gap,
+    /// What the rule replaces.
+    Input(Vec<Pattern>),
+}
+
+/// Input words escaping a rule.
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct Exceptions {
+    /// Name of the rule in the `!name` markers of the words.
+    pub label: Option<String>,
+    pub words: Vec<String>,
+    pub pattern: Option<String>,
+}
+
+/// Whether a rule changes every word.
+#[derive(Debug, Default, Serialize, PartialEq, Clone, Copy)]
+pub enum RuleApplication {
+    #[default]
+    Always,
+    /// Changes a word with this probability.
+    Probability(f64),
+    /// Keeps both the changed and the unchanged word.
+    Optional,
+}
+
+/// Where a syllable rule applies.
+/// `Any` works on the whole word with its syllable boundaries,
+/// `Stressed` and `Unstressed` on whole syllables.
+#[derive(Debug, Serialize, Eq, PartialEq, Clone, Copy)]
+pub enum SyllablePosition {
+    Any,
+    Onset,
+    Coda,
+    Stressed,
+    Unstressed,
 }
 
 #[derive(Debug, Serialize, Eq, PartialEq, Clone)]
@@ -146,6 +448,16 @@ impl SoundSystem {
             distribution,
             rules,
             phonemes_sorted,
+            inventory: Default::default(),
+            orthographies: vec![],
+            stress: Default::default(),
+            tones: vec![],
+            harmonies: vec![],
+            stages: vec![],
+            affixes: vec![],
+            paradigms: vec![],
+            roots: Default::default(),
+            names: vec![],
         }
     }
 
@@ -169,6 +481,10 @@ impl SoundSystem {
         &self.classes
     }
 
+    pub fn classes_mut(&mut self) -> &mut HashMap<String, Vec<String>> {
+        &mut self.classes
+    }
+
     pub fn distribution(&self) -> &Vec<(String, f64)> {
         &self.distribution
     }
@@ -176,6 +492,96 @@ impl SoundSystem {
     pub fn rules(&self) -> &Vec<Rule> {
         &self.rules
     }
+
+    pub fn rules_mut(&mut self) -> &mut Vec<Rule> {
+        &mut self.rules
+    }
+
+    pub fn inventory(&self) -> &phone::Inventory {
+        &self.inventory
+    }
+
+    pub fn inventory_mut(&mut self) -> &mut phone::Inventory {
+        &mut self.inventory
+    }
+
+    pub fn orthographies(&self) -> &Vec<Orthography> {
+        &self.orthographies
+    }
+
+    pub fn orthographies_mut(&mut self) -> &mut Vec<Orthography> {
+        &mut self.orthographies
+    }
+
+    pub fn stress(&self) -> &Stress {
+        &self.stress
+    }
+
+    pub fn stress_mut(&mut self) -> &mut Stress {
+        &mut self.stress
+    }
+
+    pub fn tones(&self) -> &Vec<Toneme> {
+        &self.tones
+    }
+
+    pub fn tones_mut(&mut self) -> &mut Vec<Toneme> {
+        &mut self.tones
+    }
+
+    pub fn harmonies(&self) -> &Vec<Harmony> {
+        &self.harmonies
+    }
+
+    pub fn harmonies_mut(&mut self) -> &mut Vec<Harmony> {
+        &mut self.harmonies
+    }
+
+    pub fn stages(&self) -> &Vec<Stage> {
+        &self.stages
+    }
+
+    pub fn stages_mut(&mut self) -> &mut Vec<Stage> {
+        &mut self.stages
+    }
+
+    pub fn affixes(&self) -> &Vec<Affix> {
+        &self.affixes
+    }
+
+    pub fn affixes_mut(&mut self) -> &mut Vec<Affix> {
+        &mut self.affixes
+    }
+
+    pub fn paradigms(&self) -> &Vec<Paradigm> {
+        &self.paradigms
+    }
+
+    pub fn paradigms_mut(&mut self) -> &mut Vec<Paradigm> {
+        &mut self.paradigms
+    }
+
+    pub fn roots(&self) -> &Roots {
+        &self.roots
+    }
+
+    pub fn roots_mut(&mut self) -> &mut Roots {
+        &mut self.roots
+    }
+
+    pub fn names(&self) -> &Vec<NameProfile> {
+        &self.names
+    }
+
+    pub fn names_mut(&mut self) -> &mut Vec<NameProfile> {
+        &mut self.names
+    }
+
+    pub fn stage(&self, name: &'_ str) -> Option<&Stage> {
+        self.stages
+            .iter()
+            .find(|stage| stage.name.eq_ignore_ascii_case(name))
+    }
 }
 
 fn sort_phonemes(current: &HashMap<String, Vec<PhonemeCondition>>) -> Vec<Phoneme> {
@@ -1,5 +1,6 @@
 use std::collections::HashMap;
 use std::fmt;
+use std::ops::Range;
 
 use pest::error::Error;
 use pest::Parser;
@@ -9,15 +10,99 @@ use pest::Parser;
 struct WGLParser;
 
 pub type Letter<'a> = (&'a str, f64);
+pub type PhoneDefinition<'a> = (&'a str, Vec<&'a str>);
+pub type Grapheme<'a> = (&'a str, &'a str, Condition<'a>);
+pub type Orthography<'a> = (&'a str, Vec<Grapheme<'a>>);
+/// Stress placement with the secondary stress flag.
+pub type Stress<'a> = (&'a str, bool);
+/// Tone name, phones, written mark and weight.
+pub type Toneme<'a> = (&'a str, &'a str, Option<&'a str>, f64);
+/// Harmonic sets with the neutral letters.
+pub type Harmony<'a> = (HarmonySets<'a>, Vec<&'a str>);
+/// Stage name with the position of its rules.
+pub type Stage<'a> = (&'a str, Range<usize>);
+/// Gloss of an affix with its allomorphs.
+pub type Affix<'a> = (&'a str, Vec<Allomorph<'a>>);
+/// Paradigm name with its dimensions, each one with its glosses.
+pub type Paradigm<'a> = (&'a str, Vec<(&'a str, Vec<&'a str>)>);
+/// Positions of two consonants of a root that cannot be the same,
+/// or two classes whose letters cannot follow each other in a root.
+#[derive(Debug, Clone, Copy)]
+pub enum RootException<'a> {
+    Identical(&'a str, &'a str),
+    Adjacent(&'a str, &'a str),
+}
+/// Template name with its pattern such as `C1aC2iC3`.
+pub type Template<'a> = (&'a str, &'a str);
+/// Shape of a kind of names, such as the names of rivers.
+#[derive(Debug, Default, Clone)]
+pub struct NameProfile<'a> {
+    pub name: &'a str,
+    pub syllables: Vec<Vec<&'a str>>,
+    /// Numbers of syllables with their weight.
+    pub lengths: Vec<(&'a str, Option<f64>)>,
+    pub prefixes: Vec<&'a str>,
+    pub suffixes: Vec<&'a str>,
+    pub capitalize: bool,
+}
+/// Language name, the language it descends from and its file.
+pub type Branch<'a> = (&'a str, Option<&'a str>, &'a str);
+
+/// Proto-language with its file and the languages descending from it.
+#[derive(Debug, Default)]
+pub struct FamilyDefinition<'a> {
+    pub proto: (&'a str, &'a str),
+    pub branches: Vec<Branch<'a>>,
+}
 
 #[derive(Debug, Default)]
 pub struct AST<'a> {
     pub imports: Vec<&'a str>,
+    pub notation: Option<&'a str>,
     pub letters: Vec<Letter<'a>>,
+    pub phones: Vec<PhoneDefinition<'a>>,
     pub classes: HashMap<&'a str, Vec<&'a str>>,
     pub syllables: Vec<Vec<&'a str>>,
+    pub stress: Option<Stress<'a>>,
+    pub tones: Vec<Toneme<'a>>,
+    pub harmonies: Vec<Harmony<'a>>,
     pub rules: Vec<TransformationRule<'a>>,
-    pub phonemes: HashMap<&'a str, Vec<(&'a str, Condition<'a>)>>,
+    pub stages: Vec<Stage<'a>>,
+    pub phonemes: HashMap<&'a str, Vec<(PhoneticNotation<'a>, Condition<'a>)>>,
+    pub orthographies: Vec<Orthography<'a>>,
+    pub affixes: Vec<Affix<'a>>,
+    pub paradigms: Vec<Paradigm<'a>>,
+    pub roots: Vec<Vec<&'a str>>,
+    pub root_exceptions: Vec<RootException<'a>>,
+    pub templates: Vec<Template<'a>>,
+    pub names: Vec<NameProfile<'a>>,
+}
+
+/// Letters of an allomorph written `ta-`, `<al>`, `-i`, `ka- -t` or `[CV]-`.
+#[derive(Debug, Default, Clone)]
+pub struct Allomorph<'a> {
+    pub prefix: &'a str,
+    pub infix: &'a str,
+    pub suffix: &'a str,
+    /// Reduplicant written `[σ]-` or `-[σ]`.
+    pub reduplication: Option<&'a str>,
+    /// `after` or `before` with a class or a letter.
+    pub condition: Option<(&'a str, &'a str)>,
+}
+
+/// Phones of a phoneme, between `/.../` in the file notation
+/// or between `[...]` in X-SAMPA.
+#[derive(Debug, Clone, Copy)]
+pub enum PhoneticNotation<'a> {
+    Default(&'a str),
+    XSampa(&'a str),
+}
+
+/// Vowels agree either by class or by a vowel feature.
+#[derive(Debug, Clone)]
+pub enum HarmonySets<'a> {
+    Classes(Vec<&'a str>),
+    Feature(&'a str),
 }
 
 #[derive(Debug, Clone)]
@@ -42,26 +127,53 @@ pub enum ConditionType<'a> {
 pub enum TransformationRule<'a> {
     SoundRule {
         environment: Environment<'a>,
+        /// Environment written after `//` where the rule does not apply.
+        unless: Option<&'a str>,
         input: &'a str,
         output: Option<&'a str>,
+        mode: Option<&'a str>,
+        application: Option<&'a str>,
+        label: Option<&'a str>,
+        exceptions: Vec<&'a str>,
     },
     PhonemeRule {
         input: &'a str,
         output: Option<&'a str>,
     },
+    /// Reduplication written `reduplicate [σ]-`.
+    ReduplicationRule {
+        reduplication: &'a str,
+        application: Option<&'a str>,
+        label: Option<&'a str>,
+        exceptions: Vec<&'a str>,
+    },
+    ToneRule {
+        environment: Environment<'a>,
+        input: &'a str,
+        output: Option<&'a str>,
+        application: Option<&'a str>,
+        label: Option<&'a str>,
+        exceptions: Vec<&'a str>,
+    },
 }
 
 #[derive(Debug)]
 pub enum Environment<'a> {
     All,
     Match(&'a str),
+    Onset,
+    Coda,
+    Stressed,
+    Unstressed,
 }
 
 impl<'a> TransformationRule<'a> {
     pub fn environment(&self) -> Option<&Environment<'a>> {
         match self {
-            TransformationRule::SoundRule { environment, .. } => Some(&environment),
-            TransformationRule::PhonemeRule { .. } => None,
+            TransformationRule::SoundRule { environment, .. } => Some(environment),
+            TransformationRule::ToneRule { environment, .. } => Some(environment),
+            TransformationRule::PhonemeRule { .. }
+            | TransformationRule::ReduplicationRule { .. } => None,
         }
     }
 
@@ -69,6 +181,38 @@ impl<'a> TransformationRule<'a> {
         match self {
             TransformationRule::SoundRule { input, .. } => input,
             TransformationRule::PhonemeRule { input, .. } => input,
+            TransformationRule::ToneRule { input, .. } => input,
+            TransformationRule::ReduplicationRule { reduplication, .. } => reduplication,
+        }
+    }
+
+    /// Probability or `optional` written after `@`.
+    pub fn application(&self) -> Option<&'a str> {
+        match self {
+            TransformationRule::SoundRule { application, .. } => *application,
+            TransformationRule::ToneRule { application, .. } => *application,
+            TransformationRule::ReduplicationRule { application, .. } => *application,
+            TransformationRule::PhonemeRule { .. } => None,
+        }
+    }
+
+    /// Name given before `=`, used to block the rule for some words.
+    pub fn label(&self) -> Option<&'a str> {
+        match self {
+            TransformationRule::SoundRule { label, .. } => *label,
+            TransformationRule::ToneRule { label, .. } => *label,
+            TransformationRule::ReduplicationRule { label, .. } => *label,
+            TransformationRule::PhonemeRule { .. } => None,
+        }
+    }
+
+    /// Words or `/pattern/` written after `except`.
+    pub fn exceptions(&self) -> &[&'a str] {
+        match self {
+            TransformationRule::SoundRule { exceptions, .. } => exceptions,
+            TransformationRule::ToneRule { exceptions, .. } => exceptions,
+            TransformationRule::ReduplicationRule { exceptions, .. } => exceptions,
+            TransformationRule::PhonemeRule { .. } => &[],
         }
     }
 
@@ -76,30 +220,65 @@ impl<'a> TransformationRule<'a> {
         match self {
             TransformationRule::SoundRule { output, .. } => *output,
             TransformationRule::PhonemeRule { output, .. } => *output,
+            TransformationRule::ToneRule { output, .. } => *output,
+            TransformationRule::ReduplicationRule { .. } => None,
         }
     }
 }
 
 impl<'a> fmt::Display for TransformationRule<'a> {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        let mode = match self {
-            TransformationRule::SoundRule { .. } => "->",
-            TransformationRule::PhonemeRule { .. } => "~>",
+        if let TransformationRule::ReduplicationRule { reduplication, .. } = self {
+            return write!(
+                f,
+                "{}reduplicate {}{}",
+                self.label()
+                    .map(|label| format!("{} = ", label))
+                    .unwrap_or_default(),
+                reduplication,
+                self.application()
+                    .map(|application| format!(" @ {}", application))
+                    .unwrap_or_default()
+            );
+        }
+        let (prefix, mode) = match self {
+            TransformationRule::SoundRule {
+                mode: Some(mode), ..
+            } => (format!("[{}] ", mode), "->"),
+            TransformationRule::SoundRule { .. } => ("".to_string(), "->"),
+            TransformationRule::PhonemeRule { .. } => ("".to_string(), "~>"),
+            TransformationRule::ToneRule { .. } => ("tone ".to_string(), "->"),
+            TransformationRule::ReduplicationRule { .. } => ("reduplicate ".to_string(), ""),
         };
 
         write!(
             f,
-            "{}{} {} {}",
+            "{}{}{}{} {} {}{}",
+            self.label()
+                .map(|label| format!("{} = ", label))
+                .unwrap_or_default(),
+            prefix,
             self.environment()
                 .map(|e| {
                     let mut s = String::from(e.to_string());
+                    if let TransformationRule::SoundRule {
+                        unless: Some(unless),
+                        ..
+                    } = self
+                    {
+                        s.push_str(" // ");
+                        s.push_str(unless);
+                    }
                     s.push_str(" : ");
                     s
                 })
                 .unwrap_or_else(|| "".to_string()),
             self.input(),
             mode,
-            self.output().unwrap_or("")
+            self.output().unwrap_or(""),
+            self.application()
+                .map(|application| format!(" @ {}", application))
+                .unwrap_or_default()
         )
     }
 }
@@ -109,6 +288,10 @@ impl<'a> Environment<'a> {
         match self {
             Environment::All => "_",
             Environment::Match(s) => s,
+            Environment::Onset => "onset",
+            Environment::Coda => "coda",
+            Environment::Stressed => "stressed",
+            Environment::Unstressed => "unstressed",
         }
     }
 }
@@ -121,14 +304,34 @@ pub fn from_string(input: &'_ str) -> Result<AST, Error<Rule>> {
         let rule = pair.as_rule();
         match rule {
             Rule::import => ast.imports = build_imports(pair),
+            Rule::notation => ast.notation = pair.into_inner().next().map(|p| p.as_str()),
             Rule::letters => ast.letters = build_letters(pair),
+            Rule::phones => ast.phones = build_phones(pair),
             Rule::class => {
                 let (name, values) = build_class(pair);
                 ast.classes.insert(name, values);
             }
             Rule::syllables => ast.syllables = build_syllables(pair),
-            Rule::rules => ast.rules = build_rules(pair),
+            Rule::stress => ast.stress = Some(build_stress(pair)),
+            Rule::tones => ast.tones = build_tones(pair),
+            Rule::harmony => ast.harmonies.push(build_harmony(pair)),
+            Rule::rules => ast.rules.extend(build_rules(pair)),
+            Rule::stage => {
+                let mut pairs = pair.into_inner();
+                let name = pairs.next().unwrap().as_str().trim_matches('"');
+                let start = ast.rules.len();
+                ast.rules
+                    .extend(pairs.filter_map(build_sound_or_phoneme_rule));
+                ast.stages.push((name, start..ast.rules.len()));
+            }
             Rule::phonemes => ast.phonemes = build_phonemes(pair),
+            Rule::orthography => ast.orthographies.push(build_orthography(pair)),
+            Rule::morphology => ast.affixes.extend(pair.into_inner().map(build_affix)),
+            Rule::paradigm => ast.paradigms.push(build_paradigm(pair)),
+            Rule::roots => ast.roots = build_syllables(pair),
+            Rule::root_exceptions => ast.root_exceptions = build_root_exceptions(pair),
+            Rule::templates => ast.templates = build_templates(pair),
+            Rule::names => ast.names.push(build_name_profile(pair)),
             _ => {}
         }
     }
@@ -138,14 +341,68 @@ pub fn from_string(input: &'_ str) -> Result<AST, Error<Rule>> {
     Ok(ast)
 }
 
+pub fn inventory_from_string(input: &'_ str) -> Result<Vec<PhoneDefinition<'_>>, Error<Rule>> {
+    let pairs = WGLParser::parse(Rule::inventory, input)?;
+    Ok(pairs
+        .filter(|pair| pair.as_rule() == Rule::phone_definition)
+        .map(build_phone_definition)
+        .collect())
+}
+
+pub fn family_from_string(input: &'_ str) -> Result<FamilyDefinition<'_>, Error<Rule>> {
+    let pairs = WGLParser::parse(Rule::family, input)?;
+    let mut family: FamilyDefinition = Default::default();
+    for pair in pairs {
+        match pair.as_rule() {
+            Rule::proto => {
+                let mut pairs = pair.into_inner();
+                let name = pairs.next().unwrap().as_str().trim_matches('"');
+                family.proto = (name, pairs.next().unwrap().as_str());
+            }
+            Rule::branch => {
+                let pairs = pair.into_inner().collect::<Vec<_>>();
+                let name = pairs[0].as_str().trim_matches('"');
+                let parent = if pairs.len() > 2 {
+                    Some(pairs[1].as_str().trim_matches('"'))
+                } else {
+                    None
+                };
+                family
+                    .branches
+                    .push((name, parent, pairs[pairs.len() - 1].as_str()));
+            }
+            _ => {}
+        }
+    }
+    trace!("Family: {:#?}", family);
+    Ok(family)
+}
+
+fn build_phones(pair: pest::iterators::Pair<'_, Rule>) -> Vec<PhoneDefinition<'_>> {
+    pair.into_inner().map(build_phone_definition).collect()
+}
+
+fn build_phone_definition(pair: pest::iterators::Pair<'_, Rule>) -> PhoneDefinition<'_> {
+    let mut pairs = pair.into_inner();
+    let symbol = pairs.next().unwrap().as_str();
+    let features = pairs.map(|p| p.as_str()).collect();
+    (symbol, features)
+}
+
 fn build_phonemes(
     pair: pest::iterators::Pair<'_, Rule>,
-) -> HashMap<&'_ str, Vec<(&'_ str, Condition<'_>)>> {
+) -> HashMap<&'_ str, Vec<(PhoneticNotation<'_>, Condition<'_>)>> {
     let mut result = HashMap::new();
     pair.into_inner().for_each(|phoneme_pair| {
         let mut pair = phoneme_pair.into_inner();
         let letter = pair.next().unwrap().as_str();
-        let notation = pair.next().unwrap().as_str();
+        let notation = pair
+            .next()
+            .map(|p| match p.as_rule() {
+                Rule::xsampa_notations => PhoneticNotation::XSampa(p.as_str()),
+                _ => PhoneticNotation::Default(p.as_str()),
+            })
+            .unwrap();
         let condition = pair
             .next()
             .map(build_condition)
@@ -158,6 +415,126 @@ fn build_phonemes(
     result
 }
 
+fn build_orthography(pair: pest::iterators::Pair<'_, Rule>) -> Orthography<'_> {
+    let mut pairs = pair.into_inner();
+    let name = pairs.next().unwrap().as_str().trim_matches('"');
+    let graphemes = pairs
+        .map(|grapheme_pair| {
+            let mut pair = grapheme_pair.into_inner();
+            let notation = pair.next().unwrap().as_str();
+            let letter = pair.next().unwrap().as_str();
+            let condition = pair
+                .next()
+                .map(build_condition)
+                .unwrap_or(Condition::Always);
+            (notation, letter, condition)
+        })
+        .collect();
+    (name, graphemes)
+}
+
+fn build_affix(pair: pest::iterators::Pair<'_, Rule>) -> Affix<'_> {
+    let mut pairs = pair.into_inner();
+    let gloss = pairs.next().unwrap().as_str();
+    let allomorphs = pairs
+        .map(|allomorph_pair| {
+            let mut allomorph: Allomorph = Default::default();
+            allomorph_pair.into_inner().for_each(|p| {
+                let rule = p.as_rule();
+                let letters = p
+                    .clone()
+                    .into_inner()
+                    .next()
+                    .map(|p| p.as_str())
+                    .unwrap_or("");
+                match rule {
+                    Rule::prefix => allomorph.prefix = letters,
+                    Rule::infix => allomorph.infix = letters,
+                    Rule::suffix => allomorph.suffix = letters,
+                    Rule::reduplication => allomorph.reduplication = Some(p.as_str()),
+                    _ => {
+                        let mut inner = p.into_inner();
+                        allomorph.condition = Some((
+                            inner.next().unwrap().as_str(),
+                            inner.next().unwrap().as_str(),
+                        ));
+                    }
+                }
+            });
+            allomorph
+        })
+        .collect();
+    (gloss, allomorphs)
+}
+
+fn build_paradigm(pair: pest::iterators::Pair<'_, Rule>) -> Paradigm<'_> {
+    let mut pairs = pair.into_inner();
+    let name = pairs.next().unwrap().as_str().trim_matches('"');
+    let dimensions = pairs
+        .map(|dimension| {
+            let mut pairs = dimension.into_inner();
+            let name = pairs.next().unwrap().as_str();
+            (name, pairs.map(|p| p.as_str()).collect())
+        })
+        .collect();
+    (name, dimensions)
+}
+
+fn build_root_exceptions(pair: pest::iterators::Pair<'_, Rule>) -> Vec<RootException<'_>> {
+    pair.into_inner()
+        .map(|exception| {
+            let mut pairs = exception.into_inner();
+            let left = pairs.next().unwrap();
+            let right = pairs.next().unwrap().as_str();
+            match left.as_rule() {
+                Rule::root_position => RootException::Identical(left.as_str(), right),
+                _ => RootException::Adjacent(left.as_str(), right),
+            }
+        })
+        .collect()
+}
+
+fn build_templates(pair: pest::iterators::Pair<'_, Rule>) -> Vec<Template<'_>> {
+    pair.into_inner()
+        .map(|template| {
+            let mut pairs = template.into_inner();
+            let name = pairs.next().unwrap().as_str();
+            (name, pairs.next().unwrap().as_str())
+        })
+        .collect()
+}
+
+fn build_name_profile(pair: pest::iterators::Pair<'_, Rule>) -> NameProfile<'_> {
+    let mut pairs = pair.into_inner();
+    let mut profile = NameProfile {
+        name: pairs.next().unwrap().as_str().trim_matches('"'),
+        ..Default::default()
+    };
+    pairs.for_each(|setting| match setting.as_rule() {
+        Rule::name_patterns => profile.syllables = build_syllables(setting),
+        Rule::name_length => {
+            profile.lengths = setting
+                .into_inner()
+                .map(|count| {
+                    let mut pairs = count.into_inner();
+                    let number = pairs.next().unwrap().as_str();
+                    (number, pairs.next().and_then(|p| p.as_str().parse().ok()))
+                })
+                .collect()
+        }
+        Rule::name_prefix => profile.prefixes = build_name_affixes(setting),
+        Rule::name_suffix => profile.suffixes = build_name_affixes(setting),
+        _ => profile.capitalize = true,
+    });
+    profile
+}
+
+fn build_name_affixes(pair: pest::iterators::Pair<'_, Rule>) -> Vec<&'_ str> {
+    pair.into_inner()
+        .map(|affix| affix.as_str().trim_matches('-'))
+        .collect()
+}
+
 fn build_condition_type(pair: pest::iterators::Pair<'_, Rule>) -> ConditionType<'_> {
     let inner = pair.into_inner().next().unwrap();
     match inner.as_rule() {
@@ -235,6 +612,8 @@ fn build_sound_or_phoneme_rule(
     match rule.as_rule() {
         Rule::sound_rule => Some(build_sound_rule(rule)),
         Rule::phoneme_rule => Some(build_phoneme_rule(rule)),
+        Rule::tone_rule => Some(build_tone_rule(rule)),
+        Rule::reduplication_rule => Some(build_reduplication_rule(rule)),
         _ => None,
     }
 }
@@ -253,21 +632,112 @@ fn build_phoneme_rule(pair: pest::iterators::Pair<'_, Rule>) -> TransformationRu
 }
 
 fn build_sound_rule(pair: pest::iterators::Pair<'_, Rule>) -> TransformationRule<'_> {
-    let mut pairs = pair.into_inner();
+    let mut pairs = pair.into_inner().peekable();
+    let label = match pairs.peek().map(|p| p.as_rule()) {
+        Some(Rule::rule_label) => pairs.next().map(build_label),
+        _ => None,
+    };
+    let mode = match pairs.peek().map(|p| p.as_rule()) {
+        Some(Rule::rule_mode) => pairs
+            .next()
+            .and_then(|p| p.into_inner().next())
+            .map(|p| p.as_str()),
+        _ => None,
+    };
     let environment = pairs.next().map(build_environment).unwrap();
-    let input = pairs.next().map(build_input_output).unwrap();
-    let output = pairs.next().map(build_input_output);
+    let mut input = "";
+    let mut output = None;
+    let mut application = None;
+    let mut exceptions = vec![];
+    let mut unless = None;
+    pairs.for_each(|p| match p.as_rule() {
+        Rule::environment_exception => unless = p.into_inner().next().map(|p| p.as_str()),
+        Rule::segments => input = build_input_output(p),
+        Rule::application => application = build_application(p),
+        Rule::exceptions => exceptions = build_exceptions(p),
+        _ => output = Some(build_input_output(p)),
+    });
     TransformationRule::SoundRule {
+        environment,
+        unless,
+        input,
+        output,
+        mode,
+        application,
+        label,
+        exceptions,
+    }
+}
+
+fn build_tone_rule(pair: pest::iterators::Pair<'_, Rule>) -> TransformationRule<'_> {
+    let mut pairs = pair.into_inner().peekable();
+    let label = match pairs.peek().map(|p| p.as_rule()) {
+        Some(Rule::rule_label) => pairs.next().map(build_label),
+        _ => None,
+    };
+    let environment = Environment::Match(pairs.next().unwrap().as_str());
+    let input = pairs.next().unwrap().as_str();
+    let output = pairs.next().map(|p| p.as_str());
+    let mut application = None;
+    let mut exceptions = vec![];
+    pairs.for_each(|p| match p.as_rule() {
+        Rule::application => application = build_application(p),
+        _ => exceptions = build_exceptions(p),
+    });
+    TransformationRule::ToneRule {
         environment,
         input,
         output,
+        application,
+        label,
+        exceptions,
+    }
+}
+
+fn build_reduplication_rule(pair: pest::iterators::Pair<'_, Rule>) -> TransformationRule<'_> {
+    let mut label = None;
+    let mut reduplication = "";
+    let mut application = None;
+    let mut exceptions = vec![];
+    pair.into_inner().for_each(|p| match p.as_rule() {
+        Rule::rule_label => label = Some(build_label(p)),
+        Rule::reduplication => reduplication = p.as_str(),
+        Rule::application => application = build_application(p),
+        _ => exceptions = build_exceptions(p),
+    });
+    TransformationRule::ReduplicationRule {
+        reduplication,
+        application,
+        label,
+        exceptions,
+    }
+}
+
+fn build_label(pair: pest::iterators::Pair<'_, Rule>) -> &'_ str {
+    pair.into_inner().next().unwrap().as_str()
+}
+
+fn build_exceptions(pair: pest::iterators::Pair<'_, Rule>) -> Vec<&'_ str> {
+    pair.into_inner().map(|p| p.as_str()).collect()
+}
+
+fn build_application(pair: pest::iterators::Pair<'_, Rule>) -> Option<&'_ str> {
+    let application = pair.as_str().trim_start_matches('@').trim();
+    if application.is_empty() {
+        None
+    } else {
+        Some(application)
     }
 }
 
 fn build_environment(pair: pest::iterators::Pair<'_, Rule>) -> Environment<'_> {
-    match pair.as_str() {
-        "_" => Environment::All,
-        match_rule => Environment::Match(match_rule),
+    match (pair.as_rule(), pair.as_str().to_lowercase().as_str()) {
+        (Rule::syllable_position, "onset") => Environment::Onset,
+        (Rule::syllable_position, "coda") => Environment::Coda,
+        (Rule::syllable_position, "stressed") => Environment::Stressed,
+        (Rule::syllable_position, _) => Environment::Unstressed,
+        (_, "_") => Environment::All,
+        _ => Environment::Match(pair.as_str()),
     }
 }
 
@@ -279,6 +749,46 @@ fn build_syllables(pair: pest::iterators::Pair<'_, Rule>) -> Vec<Vec<&'_ str>> {
     pair.into_inner().map(build_words).collect()
 }
 
+fn build_stress(pair: pest::iterators::Pair<'_, Rule>) -> Stress<'_> {
+    let mut pairs = pair.into_inner();
+    let placement = pairs.next().unwrap().as_str();
+    (placement, pairs.next().is_some())
+}
+
+fn build_tones(pair: pest::iterators::Pair<'_, Rule>) -> Vec<Toneme<'_>> {
+    pair.into_inner()
+        .map(|toneme| {
+            let mut name = "";
+            let mut notation = "";
+            let mut mark = None;
+            let mut weight = 0.0;
+            toneme.into_inner().for_each(|p| match p.as_rule() {
+                Rule::tone_name => name = p.as_str(),
+                Rule::phonetic_notations => notation = p.as_str(),
+                Rule::tone_mark => mark = Some(p.as_str()),
+                _ => weight = p.as_str().parse::<f64>().unwrap_or(0.0),
+            });
+            (name, notation, mark, weight)
+        })
+        .collect()
+}
+
+fn build_harmony(pair: pest::iterators::Pair<'_, Rule>) -> Harmony<'_> {
+    let mut classes = vec![];
+    let mut feature = None;
+    let mut neutral = vec![];
+    pair.into_inner().for_each(|p| match p.as_rule() {
+        Rule::harmony_feature => feature = Some(p.as_str()),
+        Rule::class_name => classes.push(p.as_str()),
+        _ => neutral.push(p.as_str()),
+    });
+    let sets = match feature {
+        Some(feature) => HarmonySets::Feature(feature),
+        None => HarmonySets::Classes(classes),
+    };
+    (sets, neutral)
+}
+
 fn build_words(pair: pest::iterators::Pair<'_, Rule>) -> Vec<&'_ str> {
     pair.into_inner().map(|p| p.as_str()).collect()
 }